@@ -2,15 +2,43 @@ use std::sync::Arc;
 use rustc_hash::FxHashMap as HashMap;
 use wana_kana::ConvertJapanese;
 
+use crate::normalize::{fold, lowercase, normalize_kana, strip};
+use crate::prefix_index::PrefixIndex;
+use crate::settings::Locale;
+
 /// 文字列キャッシュを管理する構造体
-#[derive(Default)]
 pub struct StringCache {
     /// 小文字変換のキャッシュ
     pub lowercase_cache: HashMap<Arc<String>, Arc<String>>,
     /// ひらがな変換のキャッシュ
     pub hiragana_cache: HashMap<Arc<String>, Arc<String>>,
-    /// エイリアスから文書名への逆引きインデックス
-    pub alias_to_doc: HashMap<Arc<String>, Vec<Arc<String>>>,
+    /// エイリアス（正規化済み）から文書名への逆引きインデックス
+    pub alias_to_doc: HashMap<String, Vec<Arc<String>>>,
+    /// 文書名（正規化済み）から文書名への逆引きインデックス
+    pub name_to_doc: HashMap<String, Vec<Arc<String>>>,
+    /// 照合前に取り除く文字（`IndexSettings::strip_chars` と同期する）
+    pub strip_chars: String,
+    /// 小文字化に使うロケール（`IndexSettings::locale` と同期する）
+    pub locale: Locale,
+    /// 長音・小書きのかなの表記ゆれをまとめるか（`IndexSettings::kana_normalization` と同期する）
+    pub kana_normalization: bool,
+    /// 前方一致検索用の索引（最初の前方一致検索で作り、ドキュメントが変わったら捨てる）
+    pub prefix_index: Option<PrefixIndex>,
+}
+
+impl Default for StringCache {
+    fn default() -> Self {
+        StringCache {
+            lowercase_cache: HashMap::default(),
+            hiragana_cache: HashMap::default(),
+            alias_to_doc: HashMap::default(),
+            name_to_doc: HashMap::default(),
+            strip_chars: String::new(),
+            locale: Locale::Default,
+            kana_normalization: false,
+            prefix_index: None,
+        }
+    }
 }
 
 impl StringCache {
@@ -22,18 +50,24 @@ impl StringCache {
     pub fn get_lowercase(&mut self, text: &Arc<String>) -> Arc<String> {
         self.lowercase_cache
             .entry(Arc::clone(text))
-            .or_insert_with(|| Arc::new(text.to_lowercase()))
+            .or_insert_with(|| {
+                let lower = lowercase(&strip(text, &self.strip_chars), self.locale);
+                Arc::new(if self.kana_normalization { normalize_kana(&lower) } else { lower })
+            })
             .clone()
     }
 
     /// 文字列のひらがな変換をキャッシュから取得または生成
     pub fn get_hiragana(&mut self, text: &Arc<String>) -> Option<Arc<String>> {
         // ASCII文字のみの場合、ひらがな変換を実行
-        if text.chars().all(|c| c.is_ascii()) {
+        if text.is_ascii() {
             Some(
                 self.hiragana_cache
                     .entry(Arc::clone(text))
-                    .or_insert_with(|| Arc::new(text.to_lowercase().to_hiragana()))
+                    .or_insert_with(|| {
+                        let hiragana = strip(text, &self.strip_chars).to_lowercase().to_hiragana();
+                        Arc::new(if self.kana_normalization { normalize_kana(&hiragana) } else { hiragana })
+                    })
                     .clone()
             )
         } else {
@@ -46,34 +80,50 @@ impl StringCache {
         self.lowercase_cache.clear();
         self.hiragana_cache.clear();
         self.alias_to_doc.clear();
+        self.name_to_doc.clear();
+        self.prefix_index = None;
+    }
+
+    /// 逆引きインデックスのキー（指定文字を除去して正規化した文字列。かなの表記ゆれもまとめる設定ならまとめる）
+    pub fn key(&self, text: &str) -> String {
+        let folded = fold(&strip(text, &self.strip_chars), self.locale);
+        if self.kana_normalization { normalize_kana(&folded) } else { folded }
     }
 
     /// エイリアスの逆引きインデックスに追加
     pub fn add_alias_mapping(&mut self, alias: Arc<String>, doc_name: Arc<String>) {
+        self.prefix_index = None;
+        let key = self.key(&alias);
         self.alias_to_doc
-            .entry(alias)
-            .or_insert_with(Vec::new)
+            .entry(key)
+            .or_default()
             .push(doc_name);
     }
 
     /// エイリアスの逆引きインデックスから削除
     pub fn remove_alias_mapping(&mut self, alias: &str, doc_name: &str) {
-        // Create temporary Arc for lookup
-        let alias_arc = Arc::new(alias.to_string());
-        if let Some(docs) = self.alias_to_doc.get_mut(&alias_arc) {
-            docs.retain(|d| d.as_str() != doc_name);
-        }
-        // Check if empty and remove if necessary
-        if self.alias_to_doc.get(&alias_arc).map_or(false, |docs| docs.is_empty()) {
-            self.alias_to_doc.remove(&alias_arc);
-        }
+        let key = self.key(alias);
+        remove_mapping(&mut self.alias_to_doc, &key, doc_name);
+    }
+
+    /// 文書名の逆引きインデックスに追加
+    pub fn add_name_mapping(&mut self, doc_name: Arc<String>) {
+        self.prefix_index = None;
+        let key = self.key(&doc_name);
+        self.name_to_doc
+            .entry(key)
+            .or_default()
+            .push(doc_name);
     }
 
     /// 特定のドキュメントに関連するキャッシュエントリを削除
     pub fn remove_document(&mut self, doc_name: &Arc<String>, aliases: &[Arc<String>]) {
+        self.prefix_index = None;
         // 小文字・ひらがなキャッシュから削除
         self.lowercase_cache.remove(doc_name);
         self.hiragana_cache.remove(doc_name);
+        let key = self.key(doc_name);
+        remove_mapping(&mut self.name_to_doc, &key, doc_name);
         
         // エイリアスのキャッシュも削除
         for alias in aliases {
@@ -84,6 +134,16 @@ impl StringCache {
     }
 }
 
+/// 逆引きインデックスから文書を削除し、空になったエントリも削除
+fn remove_mapping(map: &mut HashMap<String, Vec<Arc<String>>>, key: &str, doc_name: &str) {
+    if let Some(docs) = map.get_mut(key) {
+        docs.retain(|d| d.as_str() != doc_name);
+        if docs.is_empty() {
+            map.remove(key);
+        }
+    }
+}
+
 /// 検索時の優先度を表す列挙型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MatchPriority {
@@ -93,4 +153,38 @@ pub enum MatchPriority {
     AliasPrefix = 4,
     NamePartial = 5,
     AliasPartial = 6,
-}
\ No newline at end of file
+    NameFuzzyPrefix = 7,
+    AliasFuzzyPrefix = 8,
+}
+
+impl MatchPriority {
+    /// ログや分析向けの表示名
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchPriority::NameExact => "name_exact",
+            MatchPriority::AliasExact => "alias_exact",
+            MatchPriority::NamePrefix => "name_prefix",
+            MatchPriority::AliasPrefix => "alias_prefix",
+            MatchPriority::NamePartial => "name_partial",
+            MatchPriority::AliasPartial => "alias_partial",
+            MatchPriority::NameFuzzyPrefix => "name_fuzzy_prefix",
+            MatchPriority::AliasFuzzyPrefix => "alias_fuzzy_prefix",
+        }
+    }
+
+    /// 表示名から優先度を取得する
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            MatchPriority::NameExact,
+            MatchPriority::AliasExact,
+            MatchPriority::NamePrefix,
+            MatchPriority::AliasPrefix,
+            MatchPriority::NamePartial,
+            MatchPriority::AliasPartial,
+            MatchPriority::NameFuzzyPrefix,
+            MatchPriority::AliasFuzzyPrefix,
+        ]
+        .into_iter()
+        .find(|priority| priority.label() == label)
+    }
+}
@@ -0,0 +1,45 @@
+use std::cell::Cell;
+use wasm_bindgen::JsValue;
+
+use crate::search::Candidate;
+
+/// JavaScriptの比較関数 `(aId, aPriority, bId, bPriority) => number` で並べ替え、上位 `limit` 件を返す
+///
+/// 比較関数が例外を投げたり数値以外（NaN を含む）を返した場合は `None` を返す。
+/// 一貫しない比較関数でもパニックしないよう、標準のソートではなく二分探索による挿入で並べる。
+/// 比較の結果が 0 の場合は元の順序（優先度順）を保つ。
+pub fn top_by_comparator(candidates: &[Candidate], compare: &js_sys::Function, limit: usize) -> Option<Vec<Candidate>> {
+    let failed = Cell::new(false);
+    let cmp = |a: &Candidate, b: &Candidate| -> f64 {
+        if failed.get() {
+            return 0.0;
+        }
+        let args = js_sys::Array::of4(
+            &JsValue::from_str(&a.doc_name),
+            &JsValue::from(a.priority as u32),
+            &JsValue::from_str(&b.doc_name),
+            &JsValue::from(b.priority as u32),
+        );
+        match compare.apply(&JsValue::NULL, &args).ok().and_then(|v| v.as_f64()) {
+            Some(order) if !order.is_nan() => order,
+            _ => {
+                failed.set(true);
+                0.0
+            }
+        }
+    };
+
+    let mut top: Vec<Candidate> = Vec::with_capacity(limit.min(candidates.len()));
+    for candidate in candidates {
+        // 同順位のものより後ろに挿入して安定にする
+        let pos = top.partition_point(|existing| cmp(existing, candidate) <= 0.0);
+        if pos < limit {
+            top.insert(pos, candidate.clone());
+            top.truncate(limit);
+        }
+        if failed.get() {
+            return None;
+        }
+    }
+    Some(top)
+}
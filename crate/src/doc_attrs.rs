@@ -0,0 +1,132 @@
+use serde::{Serialize, Deserialize};
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+/// ドキュメントごとの付加情報
+///
+/// ダンプにはJSONとして保存されるため、項目を追加しても既存のダンプを読み込める
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocAttributes {
+    /// 名前を検索対象にしないドキュメント（エイリアスでのみ検索できる）
+    pub hidden_names: HashSet<String>,
+    /// ドキュメントのカテゴリ
+    pub categories: HashMap<String, String>,
+    /// 同じ優先度の中で上位に並べるためのブースト（大きいほど上位、未設定は 0）
+    ///
+    /// ドキュメントの削除では消えず、同じ名前のドキュメントを追加し直すと再び適用される
+    pub boosts: HashMap<String, f64>,
+    /// 追加された順序（同順位の並び替えに使う）
+    pub doc_seq: HashMap<String, u64>,
+    /// 次に追加されるドキュメントの順序番号
+    pub next_seq: u64,
+    /// ドキュメントに付けて保存するだけのメタデータ（検索には使わない）
+    pub meta: HashMap<String, serde_json::Value>,
+    /// エイリアスごとの重み（ドキュメント名 → エイリアス → 重み、未設定は 1）
+    pub alias_weights: HashMap<String, HashMap<String, f64>>,
+}
+
+impl DocAttributes {
+    pub fn is_name_searchable(&self, doc_name: &str) -> bool {
+        !self.hidden_names.contains(doc_name)
+    }
+
+    pub fn set_name_searchable(&mut self, doc_name: &str, searchable: bool) {
+        if searchable {
+            self.hidden_names.remove(doc_name);
+        } else {
+            self.hidden_names.insert(doc_name.to_string());
+        }
+    }
+
+    pub fn category(&self, doc_name: &str) -> Option<&str> {
+        self.categories.get(doc_name).map(String::as_str)
+    }
+
+    pub fn set_category(&mut self, doc_name: &str, category: Option<String>) {
+        match category {
+            Some(category) => {
+                self.categories.insert(doc_name.to_string(), category);
+            }
+            None => {
+                self.categories.remove(doc_name);
+            }
+        }
+    }
+
+    pub fn boost(&self, doc_name: &str) -> f64 {
+        self.boosts.get(doc_name).copied().unwrap_or(0.0)
+    }
+
+    /// ブーストを設定する（0 なら削除）
+    pub fn set_boost(&mut self, doc_name: &str, boost: f64) {
+        if boost == 0.0 {
+            self.boosts.remove(doc_name);
+        } else {
+            self.boosts.insert(doc_name.to_string(), boost);
+        }
+    }
+
+    pub fn meta(&self, doc_name: &str) -> Option<&serde_json::Value> {
+        self.meta.get(doc_name)
+    }
+
+    pub fn set_meta(&mut self, doc_name: &str, meta: Option<serde_json::Value>) {
+        match meta {
+            Some(meta) => {
+                self.meta.insert(doc_name.to_string(), meta);
+            }
+            None => {
+                self.meta.remove(doc_name);
+            }
+        }
+    }
+
+    pub fn alias_weight(&self, doc_name: &str, alias: &str) -> f64 {
+        self.alias_weights.get(doc_name)
+            .and_then(|weights| weights.get(alias))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// エイリアスごとの重みを設定する（1 の重みは保存しない）
+    pub fn set_alias_weights(&mut self, doc_name: &str, weights: HashMap<String, f64>) {
+        let weights: HashMap<String, f64> = weights.into_iter()
+            .filter(|&(_, weight)| weight != 1.0)
+            .collect();
+        if weights.is_empty() {
+            self.alias_weights.remove(doc_name);
+        } else {
+            self.alias_weights.insert(doc_name.to_string(), weights);
+        }
+    }
+
+    /// 追加された順序番号（番号のない古いダンプのドキュメントは 0）
+    pub fn seq(&self, doc_name: &str) -> u64 {
+        self.doc_seq.get(doc_name).copied().unwrap_or(0)
+    }
+
+    /// 新しく追加されたドキュメントに順序番号を割り当てる
+    pub fn assign_seq(&mut self, doc_name: &str) {
+        self.next_seq += 1;
+        self.doc_seq.insert(doc_name.to_string(), self.next_seq);
+    }
+
+    /// ドキュメントの付加情報をすべて削除（ブーストは残す）
+    pub fn remove_document(&mut self, doc_name: &str) {
+        self.hidden_names.remove(doc_name);
+        self.categories.remove(doc_name);
+        self.doc_seq.remove(doc_name);
+        self.meta.remove(doc_name);
+        self.alias_weights.remove(doc_name);
+    }
+
+    pub fn clear(&mut self) {
+        self.hidden_names.clear();
+        self.categories.clear();
+        self.boosts.clear();
+        self.doc_seq.clear();
+        self.next_seq = 0;
+        self.meta.clear();
+        self.alias_weights.clear();
+    }
+}
@@ -0,0 +1,61 @@
+use crate::fingerprint::checksum;
+
+/// `dump()` の出力の先頭に付ける識別子
+const MAGIC: &[u8; 4] = b"HNMI";
+/// 識別子・バージョン (u32)・本体の長さ (u64)・本体のチェックサム (u64)
+const HEADER_LEN: usize = 4 + 4 + 8 + 8;
+
+/// ヘッダーを検証できなかった理由
+pub enum EnvelopeError {
+    /// 識別子で始まっていない（ヘッダーのない旧形式の可能性がある）
+    NotEnveloped,
+    /// 読み込めるバージョンより新しい
+    VersionMismatch(u32),
+    /// ヘッダーまたは本体が途中で切れている
+    Truncated,
+    /// 本体がチェックサムと一致しない
+    ChecksumFailed,
+}
+
+/// シリアライズしたインデックスの前に識別子・バージョン・長さ・チェックサムを付ける
+pub fn wrap(version: u32, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&checksum(payload).to_le_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// ヘッダーを検証して本体を返す（`max_version` より新しいバージョンは読み込めない）
+pub fn unwrap(bytes: &[u8], max_version: u32) -> Result<&[u8], EnvelopeError> {
+    if !bytes.starts_with(MAGIC) {
+        // 識別子の途中で切れている場合も、旧形式と区別できないためここで扱う
+        return Err(if MAGIC.starts_with(bytes) && !bytes.is_empty() {
+            EnvelopeError::Truncated
+        } else {
+            EnvelopeError::NotEnveloped
+        });
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(EnvelopeError::Truncated);
+    }
+
+    let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    if version > max_version {
+        return Err(EnvelopeError::VersionMismatch(version));
+    }
+    let len = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    let expected = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+
+    let payload = &bytes[HEADER_LEN..];
+    if (payload.len() as u64) < len {
+        return Err(EnvelopeError::Truncated);
+    }
+    // 余分なバイトが付いている場合も、書き出したときの内容とは異なるため破損として扱う
+    if payload.len() as u64 != len || checksum(payload) != expected {
+        return Err(EnvelopeError::ChecksumFailed);
+    }
+    Ok(payload)
+}
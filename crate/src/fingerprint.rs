@@ -0,0 +1,84 @@
+use crate::doc_attrs::DocAttributes;
+use crate::settings::IndexSettings;
+use crate::DocAliases;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a (64bit) によるハッシュ
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// 長さを前置して書き込み、連結による衝突（"ab" + "c" と "a" + "bc"）を防ぐ
+    fn write_str(&mut self, text: &str) {
+        self.write(&(text.len() as u64).to_le_bytes());
+        self.write(text.as_bytes());
+    }
+}
+
+/// インデックスの内容から、追加順やハッシュマップの反復順に依存しないハッシュを計算する
+///
+/// ドキュメントは名前順に並べ、エイリアスは登録された順序のまま含める
+pub fn fingerprint(doc_aliases: &DocAliases, settings: &IndexSettings, attrs: &DocAttributes) -> String {
+    let mut hasher = Fnv1a::new();
+
+    let mut docs: Vec<_> = doc_aliases.iter().collect();
+    docs.sort_unstable_by_key(|&(doc_name, _)| doc_name);
+
+    hasher.write(&(docs.len() as u64).to_le_bytes());
+    for (doc_name, aliases) in docs {
+        hasher.write_str(doc_name);
+        hasher.write(&[attrs.is_name_searchable(doc_name) as u8]);
+        match attrs.category(doc_name) {
+            Some(category) => {
+                hasher.write(&[1]);
+                hasher.write_str(category);
+            }
+            None => hasher.write(&[0]),
+        }
+        match attrs.meta(doc_name) {
+            Some(meta) => {
+                hasher.write(&[1]);
+                hasher.write_str(&meta.to_string());
+            }
+            None => hasher.write(&[0]),
+        }
+        hasher.write(&(aliases.len() as u64).to_le_bytes());
+        for alias in aliases {
+            hasher.write_str(alias);
+            hasher.write(&attrs.alias_weight(doc_name, alias).to_bits().to_le_bytes());
+        }
+    }
+
+    // ブーストは存在しないドキュメントの分も保持されるため、名前順に別途含める
+    let mut boosts: Vec<_> = attrs.boosts.iter().collect();
+    boosts.sort_unstable_by_key(|&(doc_name, _)| doc_name);
+    hasher.write(&(boosts.len() as u64).to_le_bytes());
+    for (doc_name, boost) in boosts {
+        hasher.write_str(doc_name);
+        hasher.write(&boost.to_le_bytes());
+    }
+
+    // 設定も検索結果に影響するため含める（構造体のフィールド順で出力されるので安定）
+    hasher.write_str(&serde_json::to_string(settings).unwrap_or_default());
+
+    format!("{:016x}", hasher.0)
+}
+
+/// バイト列の FNV-1a (64bit) ハッシュ（保存したインデックスの破損検出用）
+pub fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(bytes);
+    hasher.0
+}
@@ -0,0 +1,80 @@
+use rustc_hash::FxHashSet as HashSet;
+
+/// 編集距離の計算（隣接文字の入れ替えも1回の編集として数える）
+///
+/// `a` と、`b` の各プレフィックスとの距離を最終行として返す
+fn distance_row(a: &[char], b: &[char]) -> Vec<usize> {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+
+    for i in 1..=a.len() {
+        let mut row = vec![0; b.len() + 1];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+
+    rows.pop().unwrap_or_default()
+}
+
+/// `target` のいずれかのプレフィックスとクエリとの最小編集距離
+///
+/// `max_distance` を超える場合は `None`
+pub fn prefix_distance(query: &[char], target: &str, max_distance: usize) -> Option<usize> {
+    let target: Vec<char> = target.chars().take(query.len() + max_distance).collect();
+    let min_len = query.len().saturating_sub(max_distance);
+    if target.len() < min_len {
+        return None;
+    }
+
+    distance_row(query, &target)[min_len..]
+        .iter()
+        .copied()
+        .min()
+        .filter(|d| *d <= max_distance)
+}
+
+/// 2つの文字列全体の編集距離
+///
+/// `max_distance` を超える場合は `None`
+pub fn edit_distance(query: &[char], target: &str, max_distance: usize) -> Option<usize> {
+    let target: Vec<char> = target.chars().collect();
+    if target.len().abs_diff(query.len()) > max_distance {
+        return None;
+    }
+
+    distance_row(query, &target)
+        .last()
+        .copied()
+        .filter(|d| *d <= max_distance)
+}
+
+/// 文字2-gramの集合（1文字以下の文字列はその文字列全体を1つのgramとする）
+fn bigrams(text: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = text.chars().collect();
+    match chars.as_slice() {
+        [] => HashSet::default(),
+        [c] => std::iter::once((*c, '\0')).collect(),
+        _ => chars.windows(2).map(|w| (w[0], w[1])).collect(),
+    }
+}
+
+/// 2-gram集合のJaccard係数（共通gram数 / 和集合のgram数）
+///
+/// 小さなタイプミスや語順の入れ替えがあっても高い値になる
+pub fn bigram_overlap(a: &str, b: &str) -> f64 {
+    let a = bigrams(a);
+    let b = bigrams(b);
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
@@ -0,0 +1,37 @@
+/// `text` の中で `needles` のいずれかに一致する範囲を、元の文字列の UTF-16 オフセット `[start, end)` で返す
+///
+/// 照合は `normalize` で1文字ずつ正規化した文字列に対して行う（取り除かれる文字や、
+/// 複数文字に展開される文字があってもオフセットは元の文字列を指す）。重なる範囲や隣接する範囲はまとめる
+pub fn find_ranges(text: &str, needles: &[&str], normalize: impl Fn(char) -> String) -> Vec<[usize; 2]> {
+    // 正規化後の各文字と、その元になった文字の UTF-16 での範囲
+    let mut units: Vec<(char, usize, usize)> = Vec::with_capacity(text.len());
+    let mut offset = 0;
+    for c in text.chars() {
+        let end = offset + c.len_utf16();
+        units.extend(normalize(c).chars().map(|n| (n, offset, end)));
+        offset = end;
+    }
+
+    let mut ranges: Vec<[usize; 2]> = Vec::new();
+    for needle in needles {
+        let needle: Vec<char> = needle.chars().collect();
+        if needle.is_empty() || needle.len() > units.len() {
+            continue;
+        }
+        for window in units.windows(needle.len()) {
+            if window.iter().zip(&needle).all(|((c, _, _), n)| c == n) {
+                ranges.push([window[0].1, window[window.len() - 1].2]);
+            }
+        }
+    }
+
+    ranges.sort_unstable();
+    let mut merged: Vec<[usize; 2]> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range[0] <= last[1] => last[1] = last[1].max(range[1]),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+use rustc_hash::FxHashSet as HashSet;
+use serde::Serialize;
+
+use crate::cache::StringCache;
+use crate::doc_attrs::DocAttributes;
+use crate::DocAliases;
+
+/// インデックスの整合性検査の結果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// 違反が1つもないか
+    pub ok: bool,
+    /// 見つかった違反の説明
+    pub violations: Vec<String>,
+}
+
+/// ドキュメント数・逆引きインデックス・付加情報がドキュメントの内容と一致しているか検査する
+///
+/// ブーストは削除したドキュメントの分も意図的に残すため検査しない
+pub fn verify(doc_aliases: &DocAliases, n_docs: usize, cache: &StringCache, attrs: &DocAttributes) -> IntegrityReport {
+    let mut violations = Vec::new();
+
+    if n_docs != doc_aliases.len() {
+        violations.push(format!("n_docs is {} but {} documents are indexed", n_docs, doc_aliases.len()));
+    }
+
+    // 各ドキュメントの名前・エイリアスが逆引きインデックスに登録されているか
+    for (doc_name, aliases) in doc_aliases {
+        if !cache.name_to_doc.get(&cache.key(doc_name)).is_some_and(|docs| docs.contains(doc_name)) {
+            violations.push(format!("name index is missing document: {}", doc_name));
+        }
+        for alias in aliases {
+            if !cache.alias_to_doc.get(&cache.key(alias)).is_some_and(|docs| docs.contains(doc_name)) {
+                violations.push(format!("alias index is missing alias {:?} of document: {}", alias, doc_name));
+            }
+        }
+    }
+
+    // 逆引きインデックスの各エントリが実在するドキュメントを指しているか
+    for (key, docs) in &cache.name_to_doc {
+        check_entry("name", key, docs, &mut violations, |doc_name| {
+            doc_aliases.contains_key(doc_name) && cache.key(doc_name) == *key
+        });
+    }
+    for (key, docs) in &cache.alias_to_doc {
+        check_entry("alias", key, docs, &mut violations, |doc_name| {
+            doc_aliases.get(doc_name)
+                .is_some_and(|aliases| aliases.iter().any(|alias| cache.key(alias) == *key))
+        });
+    }
+
+    // 削除されたドキュメントの付加情報が残っていないか
+    let exists = |doc_name: &String| doc_aliases.contains_key(doc_name);
+    for doc_name in attrs.hidden_names.iter().filter(|doc_name| !exists(doc_name)) {
+        violations.push(format!("name searchability is set for a missing document: {}", doc_name));
+    }
+    for doc_name in attrs.categories.keys().filter(|doc_name| !exists(doc_name)) {
+        violations.push(format!("category is set for a missing document: {}", doc_name));
+    }
+    for doc_name in attrs.doc_seq.keys().filter(|doc_name| !exists(doc_name)) {
+        violations.push(format!("insertion order is set for a missing document: {}", doc_name));
+    }
+    for doc_name in attrs.meta.keys().filter(|doc_name| !exists(doc_name)) {
+        violations.push(format!("metadata is set for a missing document: {}", doc_name));
+    }
+    for (doc_name, weights) in &attrs.alias_weights {
+        let Some(aliases) = doc_aliases.get(doc_name) else {
+            violations.push(format!("alias weights are set for a missing document: {}", doc_name));
+            continue;
+        };
+        for alias in weights.keys().filter(|alias| !aliases.iter().any(|a| a.as_str() == alias.as_str())) {
+            violations.push(format!("alias weight is set for a missing alias {:?} of document: {}", alias, doc_name));
+        }
+    }
+
+    violations.sort_unstable();
+    IntegrityReport { ok: violations.is_empty(), violations }
+}
+
+/// 逆引きインデックスの1エントリについて、重複や不正な参照がないか検査する
+fn check_entry(
+    index: &str,
+    key: &str,
+    docs: &[Arc<String>],
+    violations: &mut Vec<String>,
+    is_valid: impl Fn(&Arc<String>) -> bool,
+) {
+    let mut seen = HashSet::default();
+    for doc_name in docs {
+        if !seen.insert(doc_name) {
+            violations.push(format!("{} index entry {:?} lists document twice: {}", index, key, doc_name));
+        } else if !is_valid(doc_name) {
+            violations.push(format!("{} index entry {:?} points to a stale document: {}", index, key, doc_name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 整合性の取れたインデックス（cat: [neko], dog: []）
+    fn consistent() -> (DocAliases, StringCache, DocAttributes) {
+        let mut doc_aliases = DocAliases::default();
+        let mut cache = StringCache::new();
+        let mut attrs = DocAttributes::default();
+        for (name, aliases) in [("cat", vec!["neko"]), ("dog", vec![])] {
+            let doc_name = Arc::new(name.to_string());
+            let aliases: Vec<Arc<String>> = aliases.into_iter().map(|a| Arc::new(a.to_string())).collect();
+            cache.add_name_mapping(Arc::clone(&doc_name));
+            for alias in &aliases {
+                cache.add_alias_mapping(Arc::clone(alias), Arc::clone(&doc_name));
+            }
+            attrs.assign_seq(&doc_name);
+            doc_aliases.insert(doc_name, aliases);
+        }
+        (doc_aliases, cache, attrs)
+    }
+
+    fn violations(doc_aliases: &DocAliases, n_docs: usize, cache: &StringCache, attrs: &DocAttributes) -> Vec<String> {
+        let report = verify(doc_aliases, n_docs, cache, attrs);
+        assert_eq!(report.ok, report.violations.is_empty());
+        report.violations
+    }
+
+    #[test]
+    fn consistent_index_has_no_violations() {
+        let (doc_aliases, cache, attrs) = consistent();
+        assert!(violations(&doc_aliases, 2, &cache, &attrs).is_empty());
+    }
+
+    #[test]
+    fn flags_n_docs_drift() {
+        let (doc_aliases, cache, attrs) = consistent();
+        assert_eq!(
+            violations(&doc_aliases, 3, &cache, &attrs),
+            ["n_docs is 3 but 2 documents are indexed"],
+        );
+    }
+
+    #[test]
+    fn flags_missing_reverse_index_entries() {
+        let (doc_aliases, mut cache, attrs) = consistent();
+        cache.name_to_doc.remove("dog");
+        cache.alias_to_doc.remove("neko");
+        assert_eq!(
+            violations(&doc_aliases, 2, &cache, &attrs),
+            [
+                "alias index is missing alias \"neko\" of document: cat",
+                "name index is missing document: dog",
+            ],
+        );
+    }
+
+    #[test]
+    fn flags_stale_reverse_index_entries() {
+        let (doc_aliases, mut cache, attrs) = consistent();
+        cache.add_name_mapping(Arc::new("bird".to_string()));
+        cache.add_alias_mapping(Arc::new("wanko".to_string()), Arc::new("dog".to_string()));
+        assert_eq!(
+            violations(&doc_aliases, 2, &cache, &attrs),
+            [
+                "alias index entry \"wanko\" points to a stale document: dog",
+                "name index entry \"bird\" points to a stale document: bird",
+            ],
+        );
+    }
+
+    #[test]
+    fn flags_duplicate_reverse_index_entries() {
+        let (doc_aliases, mut cache, attrs) = consistent();
+        cache.add_name_mapping(Arc::new("cat".to_string()));
+        cache.add_alias_mapping(Arc::new("neko".to_string()), Arc::new("cat".to_string()));
+        assert_eq!(
+            violations(&doc_aliases, 2, &cache, &attrs),
+            [
+                "alias index entry \"neko\" lists document twice: cat",
+                "name index entry \"cat\" lists document twice: cat",
+            ],
+        );
+    }
+
+    #[test]
+    fn flags_orphan_attributes() {
+        let (doc_aliases, cache, mut attrs) = consistent();
+        attrs.set_name_searchable("bird", false);
+        attrs.set_category("bird", Some("animals".to_string()));
+        attrs.assign_seq("bird");
+        attrs.set_meta("bird", Some(serde_json::json!({ "id": 1 })));
+        attrs.set_alias_weights("bird", [("tori".to_string(), 2.0)].into_iter().collect());
+        attrs.set_alias_weights("cat", [("kitty".to_string(), 2.0)].into_iter().collect());
+        // ブーストは削除したドキュメントの分も残すため違反にならない
+        attrs.set_boost("bird", 1.0);
+        assert_eq!(
+            violations(&doc_aliases, 2, &cache, &attrs),
+            [
+                "alias weight is set for a missing alias \"kitty\" of document: cat",
+                "alias weights are set for a missing document: bird",
+                "category is set for a missing document: bird",
+                "insertion order is set for a missing document: bird",
+                "metadata is set for a missing document: bird",
+                "name searchability is set for a missing document: bird",
+            ],
+        );
+    }
+}
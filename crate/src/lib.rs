@@ -1,20 +1,39 @@
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
-use rustc_hash::FxHashMap as HashMap;
+use bincode::Options;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use std::sync::Arc;
 
 mod cache;
+mod comparator;
+mod doc_attrs;
+mod envelope;
+mod fingerprint;
+mod fuzzy;
+mod highlight;
+mod integrity;
+mod normalize;
+mod prefix_index;
+mod results;
 mod search;
+mod settings;
 
-use cache::StringCache;
-use search::SearchEngine;
+use cache::{MatchPriority, StringCache};
+use doc_attrs::DocAttributes;
+use envelope::EnvelopeError;
+use normalize::{lowercase, normalize_kana, romaji, strip};
+use results::{CategorizedResult, HighlightResult, LabeledResult, MatchDistribution, OverlapResult, RomajiResult, SearchResult, TokenHits};
+use search::{Candidate, SearchEngine, SearchFields};
+use settings::{IndexSettings, Locale, TieBreak, WhitespaceMode};
+
+type DocAliases = HashMap<Arc<String>, Vec<Arc<String>>>;
 
 // Helper module for Arc<String> serialization
 mod arc_string_serde {
     use super::*;
     use serde::{Serializer, Deserializer};
     
-    pub fn serialize<S>(map: &HashMap<Arc<String>, Vec<Arc<String>>>, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(map: &DocAliases, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
@@ -27,7 +46,7 @@ mod arc_string_serde {
         map_ser.end()
     }
     
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<Arc<String>, Vec<Arc<String>>>, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DocAliases, D::Error>
     where
         D: Deserializer<'de>,
     {
@@ -38,10 +57,44 @@ mod arc_string_serde {
     }
 }
 
+// Helper module for storing a field as JSON inside bincode
+// (bincode is not self-describing, so `#[serde(default)]` only works through JSON)
+mod json_serde {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::DeserializeOwned;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        let json = serde_json::to_string(value).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&json)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: DeserializeOwned,
+        D: Deserializer<'de>,
+    {
+        let json = String::deserialize(deserializer)?;
+        serde_json::from_str(&json).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct Doc {
     name: String,
     aliases: Vec<String>,
+    /// falseの場合、名前では検索できずエイリアスでのみ検索できる
+    #[serde(default, rename = "nameSearchable")]
+    name_searchable: Option<bool>,
+    /// カテゴリ（"animal" など）
+    #[serde(default)]
+    category: Option<String>,
+    /// 検索には使わず、保存して取り出すだけのメタデータ（ライセンスや作者など）
+    #[serde(default)]
+    meta: Option<serde_json::Value>,
 }
 
 // Root JSON structure
@@ -50,20 +103,111 @@ struct EmojisData {
     emojis: Vec<Doc>,
 }
 
+/// 重み付きのエイリアス
+#[derive(Debug, Deserialize)]
+struct WeightedAlias {
+    alias: String,
+    weight: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentUpdate {
+    name: String,
+    aliases: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentUpdates {
+    updates: Vec<DocumentUpdate>,
+}
+
+/// `transaction` で実行する操作
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum TransactionOp {
+    Add {
+        name: String,
+        aliases: Vec<String>,
+        #[serde(default, rename = "nameSearchable")]
+        name_searchable: Option<bool>,
+        #[serde(default)]
+        category: Option<String>,
+        #[serde(default)]
+        meta: Option<serde_json::Value>,
+    },
+    Remove {
+        name: String,
+    },
+    Update {
+        name: String,
+        aliases: Vec<String>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchUpdateResult {
+    updated: usize,
+    missing: Vec<String>,
+}
+
+/// クエリのJSON配列を読み込み、指定文字を除去して小文字に変換する
+fn parse_queries(query_json: &str, settings: &IndexSettings) -> Result<Vec<String>, JsValue> {
+    let original: Vec<String> = serde_json::from_str(query_json)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let removed = settings.removed_chars();
+    Ok(original.iter()
+        .map(|q| {
+            let lower = lowercase(&strip(q, &removed), settings.locale);
+            if settings.kana_normalization { normalize_kana(&lower) } else { lower }
+        })
+        .collect())
+}
+
 #[wasm_bindgen]
 #[derive(Serialize, Deserialize)]
 pub struct Index {
     #[serde(with = "arc_string_serde")]
-    doc_aliases: HashMap<Arc<String>, Vec<Arc<String>>>,
+    doc_aliases: DocAliases,
     n_docs: usize,
     #[serde(default = "default_version")]
     version: u32,
+    #[serde(with = "json_serde")]
+    settings: IndexSettings,
+    #[serde(with = "json_serde")]
+    attrs: DocAttributes,
     #[serde(skip)]
     cache: StringCache,
+    /// 名前・エイリアスごとの検索ヒット数（`None` なら集計しない）
+    #[serde(skip)]
+    match_stats: Option<HashMap<String, u64>>,
 }
 
+const INDEX_VERSION: u32 = 4;
+
 fn default_version() -> u32 {
-    2  // Current version
+    INDEX_VERSION  // Current version
+}
+
+// バージョン3のIndex構造体（マイグレーション用）
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct IndexV3 {
+    #[serde(with = "arc_string_serde")]
+    doc_aliases: DocAliases,
+    n_docs: usize,
+    version: u32,
+    #[serde(with = "json_serde")]
+    settings: IndexSettings,
+}
+
+// バージョン2のIndex構造体（マイグレーション用）
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct IndexV2 {
+    #[serde(with = "arc_string_serde")]
+    doc_aliases: DocAliases,
+    n_docs: usize,
 }
 
 // 旧バージョンのIndex構造体（マイグレーション用）
@@ -80,22 +224,41 @@ struct OldIndex {
     version: u32,
 }
 
+/// `bincode::serialize` と同じ形式で読み込み、末尾に読み残しがあれば失敗とする
+fn decode_exact<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> bincode::Result<T> {
+    bincode::options()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+}
+
 fn log_json_error(json: &str, error: &serde_json::Error) -> String {
     let error_msg = format!("JSON parse error at line {}, column {}: {}", 
                            error.line(), error.column(), error);
     
     // エラーが発生した周辺の文字列を抽出して表示
-    let context_start = std::cmp::max(0, error.column() as i64 - 20) as usize;
-    let context_end = std::cmp::min(json.len(), error.column() + 20);
-    let context = if context_end > context_start {
-        &json[context_start..context_end]
-    } else {
-        ""
-    };
+    // 列は行頭からのバイト位置なので、前後20バイトを切り出し、マルチバイト文字の途中で切らないよう文字の境界に合わせる
+    let line = json.lines().nth(error.line().saturating_sub(1)).unwrap_or("");
+    let column = error.column().min(line.len());
+    let mut start = column.saturating_sub(20);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (column + 20).min(line.len());
+    while !line.is_char_boundary(end) {
+        end += 1;
+    }
+    let context = &line[start..end];
     
     format!("{}\nContext: '{}'", error_msg, context)
 }
 
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl Index {
     #[wasm_bindgen(constructor)]
@@ -103,8 +266,11 @@ impl Index {
         Index { 
             doc_aliases: HashMap::default(),
             n_docs: 0,
-            version: 2,
+            version: INDEX_VERSION,
+            settings: IndexSettings::default(),
+            attrs: DocAttributes::default(),
             cache: StringCache::new(),
+            match_stats: None,
         }
     }
 
@@ -116,68 +282,45 @@ impl Index {
             }
         };
         
-        let emoji_count = data.emojis.len();
+        self.check_append_only(data.emojis.iter().map(|doc| doc.name.as_str()))?;
+        self.index_docs(data.emojis);
         
-        // 事前確保
-        if self.doc_aliases.is_empty() {
-            self.doc_aliases = HashMap::with_capacity_and_hasher(emoji_count, Default::default());
-        }
-        
-        for doc in data.emojis {
-            let doc_name = Arc::new(doc.name);
-            let aliases: Vec<Arc<String>> = doc.aliases.into_iter()
-                .map(Arc::new)
-                .collect();
-            
-            if self.doc_aliases.contains_key(&doc_name) { 
-                self.remove_doc(doc_name.as_ref().clone()); 
+        Ok(())
+    }
+
+    /// 1行に1つの `{ name, aliases }` を並べたNDJSONからドキュメントを追加する（空行は無視）
+    ///
+    /// すべての行を読み込んでから追加するため、不正な行があれば行番号付きのエラーを返し、何も追加しない
+    #[wasm_bindgen(js_name = "addDocumentsNdjson")]
+    pub fn add_documents_ndjson(&mut self, ndjson: &str) -> Result<(), JsValue> {
+        let mut docs = Vec::new();
+        for (i, line) in ndjson.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
             }
-            
-            self.doc_aliases.insert(Arc::clone(&doc_name), aliases);
-            self.n_docs += 1;
+            let doc: Doc = serde_json::from_str(line)
+                .map_err(|e| JsValue::from_str(&format!("Line {}: {}", i + 1, log_json_error(line, &e))))?;
+            docs.push(doc);
         }
-        
-        // キャッシュを再構築
-        self.rebuild_cache();
-        
+
+        self.check_append_only(docs.iter().map(|doc| doc.name.as_str()))?;
+        self.index_docs(docs);
+
         Ok(())
     }
 
     #[wasm_bindgen(js_name = "search")]
     pub fn search(&mut self, query_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
-        // JSONデシリアライズ
-        let original: Vec<String> = match serde_json::from_str(query_json) {
-            Ok(data) => data,
-            Err(e) => return Err(JsValue::from_str(&e.to_string())),
-        };
-        
-        let result_limit = limit.unwrap_or(10);
-        
-        if self.n_docs == 0 {
-            return Ok(serde_wasm_bindgen::to_value(&Vec::<String>::new()).unwrap());
-        }
-        
-        // クエリを小文字に変換
-        let queries: Vec<String> = original.iter().map(|q| q.to_lowercase()).collect();
-        
-        // 検索エンジンを初期化
-        let mut engine = SearchEngine {
-            doc_aliases: &self.doc_aliases,
-            cache: &mut self.cache,
-        };
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
         
         // 単一クエリの早期終了最適化は一時的に無効化
         // (romaji-to-hiragana変換に対応していないため)
         
-        // AND検索（スペース区切り）
-        if queries.len() == 1 && queries[0].contains(' ') {
-            let keywords: Vec<&str> = queries[0].split(' ').collect();
-            let results = engine.search_and(keywords, result_limit);
-            return Ok(serde_wasm_bindgen::to_value(&results).unwrap());
-        }
-        
-        // 優先度ベースの統合検索
-        let results = engine.search_unified(&queries, result_limit);
+        let results: Vec<String> = self.ranked(&queries, &cased, result_limit)
+            .into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
         Ok(serde_wasm_bindgen::to_value(&results).unwrap())
     }
 
@@ -193,47 +336,635 @@ impl Index {
         self.search(query_json, Some(limit))
     }
 
-    pub fn dump(&self) -> Result<js_sys::Uint8Array, JsValue> {
+    /// 名前またはエイリアスが完全一致するドキュメントのみを返す（名前の一致が優先）
+    ///
+    /// 大文字・小文字、全角・半角、カタカナ・ひらがなの違いは無視される
+    #[wasm_bindgen(js_name = "searchExact")]
+    pub fn search_exact(&mut self, term: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let result_limit = self.result_limit(limit);
+        let candidates = self.engine(&[]).search_exact(term, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 検索語に近い名前・エイリアスを「もしかして」の候補として最大 `max_suggestions` 件（デフォルト: 5）返す
+    ///
+    /// 3文字以上の検索語について、編集距離 2 以内の語を、その語を持つドキュメントの多い順に返す
+    pub fn suggest(&mut self, query_json: &str, max_suggestions: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let results = self.engine(&cased).suggest(&queries, max_suggestions.unwrap_or(5));
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 名前またはエイリアスが `suffix` で終わるドキュメントを返す（"*cat" のような後方一致検索）
+    ///
+    /// 後方一致用の索引は持たず、呼び出すたびにすべての名前・エイリアスを走査する（ドキュメント数に比例する O(n)）
+    #[wasm_bindgen(js_name = "searchSuffix")]
+    pub fn search_suffix(&mut self, suffix: &str, limit: Option<usize>) -> JsValue {
+        let suffix = lowercase(&strip(suffix, &self.settings.removed_chars()), self.settings.locale);
+        let result_limit = self.result_limit(limit);
+        let candidates = self.engine(&[]).search_suffix(&suffix, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        serde_wasm_bindgen::to_value(&results).unwrap()
+    }
+
+    /// 名前またはエイリアスが検索語で始まるドキュメントを、前方一致検索用の索引を使って返す（オートコンプリート用）
+    ///
+    /// 空白を含まない検索語では、結果は通常の検索の完全一致・前方一致と同じ。空白を含む検索語は AND 検索に分割せず、
+    /// 空白も含めた前方一致として照合する。索引は最初の呼び出しで作り、ドキュメントが変わると作り直す
+    #[wasm_bindgen(js_name = "searchPrefixFast")]
+    pub fn search_prefix_fast(&mut self, query_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let candidates = self.engine(&cased).search_prefix_indexed(&queries, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// `searchPrefixFast` の索引を今すぐ作る（最初の検索での待ち時間を避けるため）
+    #[wasm_bindgen(js_name = "buildPrefixIndex")]
+    pub fn build_prefix_index(&mut self) {
+        self.engine(&[]).build_prefix_index();
+    }
+
+    /// 名前またはエイリアスが指定した文字（絵文字など）そのものであるドキュメントを返す（名前の一致が優先）
+    ///
+    /// ZWJ で結合された絵文字の並びも1つの文字として照合する。前後の空白は無視される
+    #[wasm_bindgen(js_name = "searchByChar")]
+    pub fn search_by_char(&mut self, ch: &str) -> JsValue {
+        let result_limit = self.result_limit(Some(usize::MAX));
+        let candidates = self.engine(&[]).search_exact(ch.trim(), result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        serde_wasm_bindgen::to_value(&results).unwrap()
+    }
+
+    /// 最も順位の高い1件のドキュメント名だけを返す（一致しなければ `null`）
+    ///
+    /// `:shortcode:` を絵文字に置き換える場合など、1件だけ必要なときに結果の並べ替えを省く
+    #[wasm_bindgen(js_name = "searchBest")]
+    pub fn search_best(&mut self, query_json: &str) -> Result<Option<String>, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let best = self.engine(&cased).search_best(&queries);
+        self.record_matches(best.as_slice());
+        Ok(best.map(|c| (*c.doc_name).clone()))
+    }
+
+    /// 2つのクエリの上位 `limit` 件（デフォルト: 10）の結果の重なりを Jaccard 係数（0〜1）で返す（評価用）
+    ///
+    /// どちらの結果も空なら 1。検索ヒット数の集計には含めない
+    #[wasm_bindgen(js_name = "resultOverlap")]
+    pub fn result_overlap(&mut self, query_a_json: &str, query_b_json: &str, limit: Option<usize>) -> Result<f64, JsValue> {
+        let result_limit = self.result_limit(limit);
+        let (queries_a, cased_a) = self.parse_queries(query_a_json)?;
+        let results_a: HashSet<Arc<String>> = self.engine(&cased_a).search_ranked(&queries_a, result_limit)
+            .into_iter()
+            .map(|c| c.doc_name)
+            .collect();
+        let (queries_b, cased_b) = self.parse_queries(query_b_json)?;
+        let results_b: HashSet<Arc<String>> = self.engine(&cased_b).search_ranked(&queries_b, result_limit)
+            .into_iter()
+            .map(|c| c.doc_name)
+            .collect();
+        let union = results_a.union(&results_b).count();
+        if union == 0 {
+            return Ok(1.0);
+        }
+        Ok(results_a.intersection(&results_b).count() as f64 / union as f64)
+    }
+
+    /// 一致するドキュメント数だけを返す（結果の順位付けや変換を行わない）
+    #[wasm_bindgen(js_name = "countMatches")]
+    pub fn count_matches(&mut self, query_json: &str) -> Result<usize, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        Ok(self.engine(&cased).count_matches(&queries))
+    }
+
+    /// 一致するすべてのドキュメント名を順不同で返す（順位付けなし）
+    ///
+    /// `setMaxLimit` で上限が設定されている場合のみ、その件数で打ち切る
+    #[wasm_bindgen(js_name = "matchedDocumentSet")]
+    pub fn matched_document_set(&mut self, query_json: &str) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let max_limit = self.settings.max_limit.unwrap_or(usize::MAX);
+        let results: Vec<String> = self.engine(&cased).matched_documents(&queries)
+            .into_iter()
+            .take(max_limit)
+            .map(|name| (*name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 一致するすべてのドキュメントについて、一致の種類ごとの件数を返す（関連度の調整用）
+    ///
+    /// `{ nameExact, aliasExact, namePrefix, aliasPrefix, namePartial, aliasPartial }` の形式
+    #[wasm_bindgen(js_name = "matchDistribution")]
+    pub fn match_distribution(&mut self, query_json: &str) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let mut distribution = MatchDistribution::default();
+        for candidate in self.engine(&cased).matched_candidates(&queries) {
+            distribution.record(candidate.priority);
+        }
+        Ok(serde_wasm_bindgen::to_value(&distribution).unwrap())
+    }
+
+    /// 一致するドキュメントが1件でもあるかだけを返す（最初の一致が見つかった時点で終了する）
+    #[wasm_bindgen(js_name = "hasAnyMatch")]
+    pub fn has_any_match(&mut self, query_json: &str) -> Result<bool, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        Ok(self.engine(&cased).has_any_match(&queries))
+    }
+
+    /// 検索結果を一致の種類（"name_exact", "alias_prefix" など）とともに返す
+    #[wasm_bindgen(js_name = "searchWithLabels")]
+    pub fn search_with_labels(&mut self, query_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let results: Vec<LabeledResult> = self.ranked(&queries, &cased, result_limit)
+            .into_iter()
+            .map(|c| LabeledResult {
+                id: (*c.doc_name).clone(),
+                priority: c.priority.label(),
+            })
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 検索結果を `SearchResult` クラスの配列として返す
+    #[wasm_bindgen(js_name = "searchTyped")]
+    pub fn search_typed(&mut self, query_json: &str, limit: Option<usize>) -> Result<Vec<SearchResult>, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        Ok(self.ranked(&queries, &cased, result_limit)
+            .into_iter()
+            .map(|c| SearchResult::new((*c.doc_name).clone(), c.priority))
+            .collect())
+    }
+
+    /// 通常の検索結果のうち、`boost_terms_json`（文字列の配列）のいずれかを名前・エイリアスに含むものを上位にする
+    ///
+    /// ブースト語に一致するドキュメントのブーストに `weight` を加える。一致するドキュメントは増えず、
+    /// 一致の種類による順位も変わらない
+    #[wasm_bindgen(js_name = "searchWithBoostTerms")]
+    pub fn search_with_boost_terms(&mut self, query_json: &str, boost_terms_json: &str, weight: f64, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        if !weight.is_finite() {
+            return Err(JsValue::from_str(&format!("Boost must be a finite number: {}", weight)));
+        }
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let boost_terms = parse_queries(boost_terms_json, &self.settings)?;
+        let result_limit = self.result_limit(limit);
+        let candidates = self.engine(&cased).search_ranked_with_boost_terms(&queries, &boost_terms, weight, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 照合の対象にするフィールドを `fields_json`（`["name"]`、`["alias"]`、`["name", "alias"]`）で指定して検索する
+    ///
+    /// 空の配列は両方を対象にする。名前を対象にしても `nameSearchable` が false のドキュメントの名前は照合しない
+    #[wasm_bindgen(js_name = "searchFields")]
+    pub fn search_fields(&mut self, query_json: &str, fields_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let fields: Vec<String> = serde_json::from_str(fields_json)
+            .map_err(|e| JsValue::from_str(&log_json_error(fields_json, &e)))?;
+        let fields = SearchFields::parse(&fields).map_err(|e| JsValue::from_str(&e))?;
+        let result_limit = self.result_limit(limit);
+        let mut engine = self.engine(&cased);
+        engine.fields = fields;
+        let candidates = engine.search_ranked(&queries, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 検索結果を、名前と一致したエイリアスの中で検索語に一致した範囲（UTF-16 オフセット）とともに返す
+    ///
+    /// エイリアスでの一致なら `matchedAlias` にそのエイリアスが入り、名前での一致なら `null`
+    #[wasm_bindgen(js_name = "searchWithHighlights")]
+    pub fn search_with_highlights(&mut self, query_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let candidates = self.ranked(&queries, &cased, result_limit);
+        let engine = self.engine(&cased);
+        let results: Vec<HighlightResult> = candidates.into_iter()
+            .map(|c| {
+                let matched_alias = (!Arc::ptr_eq(&c.doc_name, &c.matched)).then(|| (*c.matched).clone());
+                let alias_ranges = matched_alias.as_deref()
+                    .map(|alias| engine.highlight(alias, &queries))
+                    .unwrap_or_default();
+                HighlightResult {
+                    id: (*c.doc_name).clone(),
+                    name_ranges: engine.highlight(&c.doc_name, &queries),
+                    matched_alias,
+                    alias_ranges,
+                }
+            })
+            .collect();
+        Ok(results.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).unwrap())
+    }
+
+    /// 検索結果をドキュメントのカテゴリとともに返す（カテゴリのないドキュメントは `null`）
+    #[wasm_bindgen(js_name = "searchWithCategory")]
+    pub fn search_with_category(&mut self, query_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let results: Vec<CategorizedResult> = self.ranked(&queries, &cased, result_limit)
+            .into_iter()
+            .map(|c| CategorizedResult {
+                id: (*c.doc_name).clone(),
+                category: self.attrs.category(&c.doc_name).map(str::to_string),
+            })
+            .collect();
+        Ok(results.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).unwrap())
+    }
+
+    /// 検索結果をドキュメント名のローマ字表記とともに `{ id, romaji }` の配列で返す（表示用）
+    ///
+    /// かなを含まない名前はそのまま返す
+    #[wasm_bindgen(js_name = "searchWithRomaji")]
+    pub fn search_with_romaji(&mut self, query_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let results: Vec<RomajiResult> = self.ranked(&queries, &cased, result_limit)
+            .into_iter()
+            .map(|c| RomajiResult { id: (*c.doc_name).clone(), romaji: romaji(&c.doc_name) })
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 一致の種類が `min_priority`（"name_prefix" など）以上のドキュメントだけを返す
+    ///
+    /// 例えば "alias_prefix" を指定すると完全一致と前方一致のみになり、部分一致は含まれない
+    #[wasm_bindgen(js_name = "searchMinPriority")]
+    pub fn search_min_priority(&mut self, query_json: &str, min_priority: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let floor = MatchPriority::from_label(min_priority)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown match priority: {}", min_priority)))?;
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let mut candidates = self.engine(&cased).search_ranked(&queries, result_limit);
+        candidates.retain(|c| c.priority <= floor);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// `categories_json`（カテゴリ名の配列）のいずれかに属するドキュメントだけを検索する
+    ///
+    /// 空の配列を指定した場合は絞り込まずにすべてを検索する
+    #[wasm_bindgen(js_name = "searchInCategories")]
+    pub fn search_in_categories(&mut self, query_json: &str, categories_json: &str, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let categories: HashSet<String> = serde_json::from_str(categories_json)
+            .map_err(|e| JsValue::from_str(&log_json_error(categories_json, &e)))?;
+        let result_limit = self.result_limit(limit);
+        let candidates = if categories.is_empty() {
+            self.ranked(&queries, &cased, result_limit)
+        } else {
+            let all_docs = self.doc_aliases.len();
+            let mut all = self.engine(&cased).search_ranked(&queries, all_docs);
+            all.retain(|c| self.attrs.category(&c.doc_name).is_some_and(|category| categories.contains(category)));
+            all.truncate(result_limit);
+            self.record_matches(&all);
+            all
+        };
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 1つのカテゴリから返す結果を最大 `max_per_category` 件に制限して検索する
+    ///
+    /// 上限に達したカテゴリのドキュメントは飛ばし、他のカテゴリの次に順位の高いドキュメントで埋める。
+    /// カテゴリのないドキュメントは制限しない
+    #[wasm_bindgen(js_name = "searchDiversified")]
+    pub fn search_diversified(&mut self, query_json: &str, limit: Option<usize>, max_per_category: usize) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let all_docs = self.doc_aliases.len();
+        let mut per_category: HashMap<&str, usize> = HashMap::default();
+        let mut picked = Vec::new();
+        for c in self.engine(&cased).search_ranked(&queries, all_docs) {
+            if picked.len() >= result_limit {
+                break;
+            }
+            if let Some(category) = self.attrs.category(&c.doc_name) {
+                let count = per_category.entry(category).or_default();
+                if *count >= max_per_category {
+                    continue;
+                }
+                *count += 1;
+            }
+            picked.push(c);
+        }
+        self.record_matches(&picked);
+        let results: Vec<String> = picked.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 並び順を指定して検索する（"relevance": 優先度順、"alpha": 名前順）
+    ///
+    /// "alpha" でも一致するドキュメントの集合は通常の検索と同じで、一致したすべての中から名前順に `limit` 件を返す
+    #[wasm_bindgen(js_name = "searchSorted")]
+    pub fn search_sorted(&mut self, query_json: &str, limit: Option<usize>, sort_mode: &str) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let candidates = match sort_mode {
+            "relevance" => self.ranked(&queries, &cased, result_limit),
+            "alpha" => {
+                let all_docs = self.doc_aliases.len();
+                let mut all = self.engine(&cased).search_ranked(&queries, all_docs);
+                all.sort_by(|a, b| a.doc_name.cmp(&b.doc_name));
+                all.truncate(result_limit);
+                self.record_matches(&all);
+                all
+            }
+            _ => return Err(JsValue::from_str(&format!("Unknown sort mode: {}", sort_mode))),
+        };
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 検索結果を (ID, 優先度) の組としてbincodeでシリアライズして返す
+    ///
+    /// 大量の結果をWorkerへ渡す場合に、JSオブジェクトの生成を避けるためのもの
+    #[wasm_bindgen(js_name = "searchBinary")]
+    pub fn search_binary(&mut self, query_json: &str, limit: Option<usize>) -> Result<js_sys::Uint8Array, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let results: Vec<(String, u8)> = self.ranked(&queries, &cased, result_limit)
+            .into_iter()
+            .map(|c| ((*c.doc_name).clone(), c.priority as u8))
+            .collect();
         Ok(js_sys::Uint8Array::from(
-            &bincode::serialize(self).map_err(|e| JsValue::from_str(&e.to_string()))?[..],
+            &bincode::serialize(&results).map_err(|e| JsValue::from_str(&e.to_string()))?[..],
         ))
     }
+
+    /// 検索結果をJavaScriptの比較関数 `(aId, aPriority, bId, bPriority) => number` で並べ替えて返す
+    ///
+    /// 優先度は一致の種類を表す数値（1〜8、小さいほど上位）。
+    /// 比較関数が例外を投げたり数値以外を返した場合は、通常の優先度順にフォールバックする
+    #[wasm_bindgen(js_name = "searchWithComparator")]
+    pub fn search_with_comparator(&mut self, query_json: &str, limit: Option<usize>, compare: &js_sys::Function) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let all_docs = self.doc_aliases.len();
+        let mut candidates = self.engine(&cased).search_ranked(&queries, all_docs);
+        let top = match comparator::top_by_comparator(&candidates, compare, result_limit) {
+            Some(sorted) => sorted,
+            None => {
+                candidates.truncate(result_limit);
+                candidates
+            }
+        };
+        self.record_matches(&top);
+        let results: Vec<String> = top.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 名前・エイリアスとの文字2-gramの重なり（Jaccard係数、0〜1）で検索する
+    ///
+    /// `min_overlap` 以上のドキュメントを `{ id, overlap }` の配列として重なりの大きい順に返す
+    #[wasm_bindgen(js_name = "searchByGramOverlap")]
+    pub fn search_by_gram_overlap(&mut self, query_json: &str, min_overlap: f64, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let matches = self.engine(&cased).search_gram_overlap(&queries, min_overlap, result_limit);
+        self.record_tokens(matches.iter().map(|(_, matched, _)| matched));
+        let results: Vec<OverlapResult> = matches.into_iter()
+            .map(|(name, _, overlap)| OverlapResult { id: (*name).clone(), overlap })
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// タイプミスを許容する前方一致検索（オートコンプリート用）
+    ///
+    /// 編集距離は最大1、3文字未満のクエリは通常の前方一致のみ
+    #[wasm_bindgen(js_name = "searchFuzzyPrefix")]
+    pub fn search_fuzzy_prefix(&mut self, query_json: &str, max_distance: usize, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let candidates = self.engine(&cased).search_fuzzy_prefix(&queries, max_distance, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 空白で区切ったすべての検索語が、名前またはエイリアスに含まれるか、タイプミスを許容して前方一致するドキュメントを返す
+    ///
+    /// 編集距離は最大1、3文字未満の検索語はタイプミスを許容しない
+    #[wasm_bindgen(js_name = "searchFuzzyAnd")]
+    pub fn search_fuzzy_and(&mut self, query_json: &str, max_distance: usize, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let (queries, cased) = self.parse_queries(query_json)?;
+        let result_limit = self.result_limit(limit);
+        let candidates = self.engine(&cased).search_fuzzy_and(&queries, max_distance, result_limit);
+        self.record_matches(&candidates);
+        let results: Vec<String> = candidates.into_iter()
+            .map(|c| (*c.doc_name).clone())
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// インデックスをバイナリ形式にシリアライズする
+    ///
+    /// 先頭に識別子・バージョン・長さ・チェックサムを付け、`load()` で破損を検出できるようにする
+    pub fn dump(&self) -> Result<js_sys::Uint8Array, JsValue> {
+        let payload = bincode::serialize(self).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(js_sys::Uint8Array::from(&envelope::wrap(INDEX_VERSION, &payload)[..]))
+    }
+
+    /// `dump()` の出力からインデックスを読み込む
+    ///
+    /// 読み込めない場合は "not a hanami index"・"version mismatch"・"checksum failed"・"truncated"
+    /// のいずれかを含むエラーを返す。ヘッダーのない旧形式の出力もそのまま読み込める
     pub fn load(bytes: js_sys::Uint8Array) -> Result<Index, JsValue> {
         let bytes_vec = bytes.to_vec();
+        let mut index = match envelope::unwrap(&bytes_vec, INDEX_VERSION) {
+            Ok(payload) => Self::deserialize_any_version(payload)?,
+            Err(EnvelopeError::NotEnveloped) => Self::deserialize_legacy(&bytes_vec)
+                .ok_or_else(|| JsValue::from_str("Failed to load index: not a hanami index"))?,
+            Err(EnvelopeError::VersionMismatch(version)) => return Err(JsValue::from_str(&format!(
+                "Failed to load index: version mismatch (index version {}, supported up to {})",
+                version, INDEX_VERSION
+            ))),
+            Err(EnvelopeError::Truncated) => return Err(JsValue::from_str("Failed to load index: truncated")),
+            Err(EnvelopeError::ChecksumFailed) => return Err(JsValue::from_str("Failed to load index: checksum failed")),
+        };
         
-        // まず新しい形式で読み込みを試みる
-        match bincode::deserialize::<Index>(&bytes_vec) {
-            Ok(mut index) => {
-                // キャッシュを再構築
-                index.rebuild_cache();
-                Ok(index)
-            },
-            Err(_) => {
-                // 失敗したら旧形式として読み込みを試みる
-                match bincode::deserialize::<OldIndex>(&bytes_vec) {
-                    Ok(old_index) => {
-                        // 旧形式から新形式へマイグレーション
-                        let mut index = Index {
-                            doc_aliases: old_index.doc_aliases.into_iter()
-                                .map(|(k, v)| {
-                                    (Arc::new(k), v.into_iter().map(Arc::new).collect())
-                                })
-                                .collect(),
-                            n_docs: old_index.n_docs,
-                            version: 2,
-                            cache: StringCache::new(),
-                        };
-                        // キャッシュを再構築
-                        index.rebuild_cache();
-                        Ok(index)
-                    }
-                    Err(e) => Err(JsValue::from_str(&format!(
-                        "Failed to load index: {}. The index format may be incompatible.",
-                        e
-                    )))
-                }
-            }
+        // 保存されていた件数ではなく実際のドキュメント数に合わせる
+        index.n_docs = index.doc_aliases.len();
+        // キャッシュを再構築
+        index.rebuild_cache();
+        Ok(index)
+    }
+
+    /// カテゴリに属するドキュメント名を名前順に返す（検索クエリなしの一覧表示用）
+    ///
+    /// `offset` 件を読み飛ばしてから最大 `limit` 件（デフォルト: 10）を返す。未知のカテゴリは空
+    #[wasm_bindgen(js_name = "documentsInCategory")]
+    pub fn documents_in_category(&self, category: &str, limit: Option<usize>, offset: Option<usize>) -> Result<JsValue, JsValue> {
+        let mut names: Vec<&String> = self.attrs.categories.iter()
+            .filter(|(_, doc_category)| doc_category.as_str() == category)
+            .map(|(doc_name, _)| doc_name)
+            .collect();
+        names.sort_unstable();
+        let results: Vec<&String> = names.into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(self.result_limit(limit))
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    }
+
+    /// 指定したエイリアスを持つドキュメント名を名前順に返す（検索ではなく逆引き）
+    ///
+    /// エイリアスは検索時と同じく指定文字の除去と小文字化をしてから照合する
+    #[wasm_bindgen(js_name = "docsForAlias")]
+    pub fn docs_for_alias(&self, alias: &str) -> JsValue {
+        let mut names: Vec<&str> = self.cache.alias_to_doc
+            .get(&self.cache.key(alias))
+            .map(|docs| docs.iter().map(|doc| doc.as_str()).collect())
+            .unwrap_or_default();
+        names.sort_unstable();
+        serde_wasm_bindgen::to_value(&names).unwrap()
+    }
+
+    /// エイリアスを1つも持たないドキュメント名を名前順に最大 `limit` 件（デフォルト: 10）返す
+    ///
+    /// 検索されにくいドキュメントを洗い出し、エイリアスの整備を優先すべきものを見つけるため
+    #[wasm_bindgen(js_name = "documentsWithoutAliases")]
+    pub fn documents_without_aliases(&self, limit: Option<usize>) -> JsValue {
+        let mut names: Vec<&str> = self.doc_aliases.iter()
+            .filter(|(_, aliases)| aliases.is_empty())
+            .map(|(doc_name, _)| doc_name.as_str())
+            .collect();
+        names.sort_unstable();
+        names.truncate(self.result_limit(limit));
+        serde_wasm_bindgen::to_value(&names).unwrap()
+    }
+
+    /// `{ [docName]: number }` 形式でブーストをまとめて設定する（既存のブーストはすべて置き換える）
+    ///
+    /// ブーストは同じ優先度の中での順位にだけ影響し、大きいほど上位になる。
+    /// 存在しないドキュメントの値も保持され、後からそのドキュメントが追加されると適用される
+    #[wasm_bindgen(js_name = "setBoosts")]
+    pub fn set_boosts(&mut self, boosts_json: &str) -> Result<(), JsValue> {
+        let boosts: HashMap<String, f64> = serde_json::from_str(boosts_json)
+            .map_err(|e| JsValue::from_str(&log_json_error(boosts_json, &e)))?;
+        self.attrs.boosts.clear();
+        for (doc_name, boost) in boosts {
+            self.attrs.set_boost(&doc_name, boost);
+        }
+        Ok(())
+    }
+
+    /// 1つのドキュメントのブーストを設定する（デフォルト: 0）
+    ///
+    /// ブーストは加算的な値で、同じ優先度の中で大きいほど上位、負の値なら 0 のドキュメントより下位になる。
+    /// NaN や無限大はエラーになる
+    #[wasm_bindgen(js_name = "setBoost")]
+    pub fn set_boost(&mut self, doc_id: &str, boost: f64) -> Result<(), JsValue> {
+        if !boost.is_finite() {
+            return Err(JsValue::from_str(&format!("Boost must be a finite number: {}", boost)));
+        }
+        self.attrs.set_boost(doc_id, boost);
+        Ok(())
+    }
+
+    /// 追記専用モードを設定する（デフォルト: 無効）
+    ///
+    /// 有効にすると、既存のドキュメントと同じ名前を追加しようとしたときに置き換えずにエラーを返す
+    #[wasm_bindgen(js_name = "setAppendOnly")]
+    pub fn set_append_only(&mut self, enabled: bool) {
+        self.settings.append_only = enabled;
+    }
+
+    /// 単語の先頭（名前・エイリアスの先頭、または `_` `-` 空白 `.` の直後）で一致した結果に加えるブーストを設定する（デフォルト: 0）
+    ///
+    /// "cat" で検索したとき、"bluecat" より "happy_cat" を上位にする。同じ優先度の中での順位にだけ影響する
+    #[wasm_bindgen(js_name = "setWordStartBonus")]
+    pub fn set_word_start_bonus(&mut self, weight: f64) -> Result<(), JsValue> {
+        if !weight.is_finite() {
+            return Err(JsValue::from_str(&format!("Word start bonus must be a finite number: {}", weight)));
         }
+        self.settings.word_start_bonus = weight;
+        Ok(())
+    }
+
+    /// ドキュメントのメタデータを返す（メタデータがない、またはドキュメントが存在しない場合は `null`）
+    #[wasm_bindgen(js_name = "getMeta")]
+    pub fn get_meta(&self, doc_id: &str) -> JsValue {
+        self.attrs.meta(doc_id)
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap()
+    }
+
+    #[wasm_bindgen(js_name = "getDocumentCount")]
+    pub fn get_document_count(&self) -> usize {
+        self.n_docs
+    }
+
+    /// 自分の名前で検索しても部分一致にしかならないドキュメント名を、名前順に最大 `limit` 件（デフォルト: 10）返す
+    ///
+    /// 名前が検索できない、空白を含むためAND検索になる、などの理由で見つけにくいドキュメントを点検するため
+    #[wasm_bindgen(js_name = "documentsOnlyPartiallySearchable")]
+    pub fn documents_only_partially_searchable(&mut self, limit: Option<usize>) -> JsValue {
+        let result_limit = self.result_limit(limit);
+        let mut names: Vec<String> = self.engine(&[]).only_partially_self_searchable()
+            .into_iter()
+            .map(|name| (*name).clone())
+            .collect();
+        names.sort_unstable();
+        names.truncate(result_limit);
+        serde_wasm_bindgen::to_value(&names).unwrap()
+    }
+
+    /// ドキュメントの長さ（名前とエイリアスの合計数）を返す。存在しない場合は `null`
+    #[wasm_bindgen(js_name = "getDocLen")]
+    pub fn get_doc_len(&self, doc_id: &str) -> Option<usize> {
+        self.doc_aliases.get(&Arc::new(doc_id.to_string())).map(|aliases| aliases.len() + 1)
+    }
+
+    /// インデックスの内容（ドキュメント・エイリアス・設定）の安定したハッシュを16進文字列で返す
+    ///
+    /// 同じドキュメントを異なる順序で追加しても同じ値になる
+    pub fn fingerprint(&self) -> String {
+        fingerprint::fingerprint(&self.doc_aliases, &self.settings, &self.attrs)
+    }
+
+    /// インデックス内部の整合性を検査し、`{ ok, violations }` を返す（CIでの検証用）
+    ///
+    /// ドキュメント数、名前・エイリアスの逆引きインデックス、ドキュメントごとの付加情報が
+    /// ドキュメントの内容と一致しているかを調べる
+    #[wasm_bindgen(js_name = "verifyIntegrity")]
+    pub fn verify_integrity(&self) -> JsValue {
+        let report = integrity::verify(&self.doc_aliases, self.n_docs, &self.cache, &self.attrs);
+        serde_wasm_bindgen::to_value(&report).unwrap()
     }
 
     fn remove_doc(&mut self, doc_id: String) {
@@ -241,8 +972,10 @@ impl Index {
         if let Some(aliases) = self.doc_aliases.remove(&doc_id_arc) {
             // キャッシュから削除
             self.cache.remove_document(&doc_id_arc, &aliases);
+            self.attrs.remove_document(&doc_id_arc);
             self.n_docs = self.n_docs.saturating_sub(1);
         }
+        debug_assert_eq!(self.n_docs, self.doc_aliases.len());
     }
 
     #[wasm_bindgen(js_name = "removeDocument")]
@@ -256,27 +989,60 @@ impl Index {
         }
     }
 
+    /// ブーストの大きい上位 `k` 件のドキュメントだけを残し、それ以外を削除する（同じブーストは名前順）
+    ///
+    /// 人気のあるドキュメントだけを含む小さなインデックスをビルド時に作るためのもの。削除した件数を返す
+    #[wasm_bindgen(js_name = "retainTop")]
+    pub fn retain_top(&mut self, k: usize) -> usize {
+        let mut ranked: Vec<Arc<String>> = self.doc_aliases.keys().cloned().collect();
+        ranked.sort_by(|a, b| {
+            self.attrs.boost(b).total_cmp(&self.attrs.boost(a)).then_with(|| a.cmp(b))
+        });
+        let removed = ranked.split_off(k.min(ranked.len()));
+        for doc_name in &removed {
+            self.remove_doc((**doc_name).clone());
+        }
+        removed.len()
+    }
+
+    /// `name_searchable` が false の場合、名前では検索できずエイリアスでのみ検索できる（デフォルト: true）
     #[wasm_bindgen(js_name = "addDocument")]
-    pub fn add_document(&mut self, name: &str, aliases_json: &str) -> Result<(), JsValue> {
+    pub fn add_document(&mut self, name: &str, aliases_json: &str, name_searchable: Option<bool>, category: Option<String>) -> Result<(), JsValue> {
         let aliases: Vec<String> = serde_json::from_str(aliases_json)
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
         
-        let doc_name = Arc::new(name.to_string());
-        let arc_aliases: Vec<Arc<String>> = aliases.into_iter()
-            .map(Arc::new)
-            .collect();
+        self.check_append_only([name])?;
+        self.insert_doc(name, aliases, name_searchable.unwrap_or(true), category, None);
         
-        // 既存のドキュメントなら削除
-        if self.doc_aliases.contains_key(&doc_name) { 
-            self.remove_doc(name.to_string()); 
+        Ok(())
+    }
+
+    /// 重み付きのエイリアス（`[{ alias, weight }]`）でドキュメントを追加する
+    ///
+    /// 同じ種類の一致の中では、重みの大きいエイリアスで一致したドキュメントが上位になる（ブーストの次に比較）。
+    /// 重みは正の有限数で、`addDocument` のエイリアスの重みは 1
+    #[wasm_bindgen(js_name = "addDocumentWeighted")]
+    pub fn add_document_weighted(&mut self, name: &str, weighted_aliases_json: &str) -> Result<(), JsValue> {
+        let weighted: Vec<WeightedAlias> = serde_json::from_str(weighted_aliases_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if let Some(invalid) = weighted.iter().find(|w| !(w.weight.is_finite() && w.weight > 0.0)) {
+            return Err(JsValue::from_str(&format!("Alias weight must be a positive finite number: {} ({})", invalid.alias, invalid.weight)));
         }
-        
-        // キャッシュを更新
-        self.update_cache_for_document(&doc_name, &arc_aliases);
-        
-        self.doc_aliases.insert(doc_name, arc_aliases);
-        self.n_docs += 1;
-        
+
+        self.check_append_only([name])?;
+        let mut weights: HashMap<String, f64> = HashMap::default();
+        let aliases: Vec<String> = weighted.into_iter()
+            .map(|w| {
+                weights.entry(w.alias.clone()).or_insert(w.weight);
+                w.alias
+            })
+            .collect();
+        self.insert_doc(name, aliases, true, None, None);
+
+        // 重複としてまとめられたエイリアスの重みは捨てる
+        let kept = &self.doc_aliases[&Arc::new(name.to_string())];
+        weights.retain(|alias, _| kept.iter().any(|a| a.as_str() == alias.as_str()));
+        self.attrs.set_alias_weights(name, weights);
         Ok(())
     }
 
@@ -290,16 +1056,88 @@ impl Index {
         }
         
         // エイリアスの検証
-        let _: Vec<String> = serde_json::from_str(aliases_json)
+        let aliases: Vec<String> = serde_json::from_str(aliases_json)
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
         
-        // アップデート前のドキュメントを削除
-        self.remove_doc(doc_id.to_string());
+        Ok(self.update_doc(doc_id, aliases))
+    }
+
+    /// 名前で検索できるかどうかだけを切り替える（エイリアスはそのまま）。存在しなければ false
+    #[wasm_bindgen(js_name = "setNameSearchable")]
+    pub fn set_name_searchable(&mut self, doc_id: &str, enabled: bool) -> bool {
+        if !self.doc_aliases.contains_key(&Arc::new(doc_id.to_string())) {
+            return false;
+        }
+        self.attrs.set_name_searchable(doc_id, enabled);
+        true
+    }
+
+    /// 複数ドキュメントのエイリアスをまとめて更新する
+    ///
+    /// `{ updates: [{ name, aliases }] }` 形式を受け取り、更新した件数と存在しなかった名前を返す
+    #[wasm_bindgen(js_name = "updateDocumentsBatch")]
+    pub fn update_documents_batch(&mut self, json: &str) -> Result<JsValue, JsValue> {
+        let data: DocumentUpdates = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&log_json_error(json, &e)))?;
         
-        // 新しいドキュメントを追加
-        self.add_document(doc_id, aliases_json)?;
+        let mut result = BatchUpdateResult { updated: 0, missing: Vec::new() };
+        for update in data.updates {
+            if self.update_doc(&update.name, update.aliases) {
+                result.updated += 1;
+            } else {
+                result.missing.push(update.name);
+            }
+        }
         
-        Ok(true)
+        Ok(serde_wasm_bindgen::to_value(&result).unwrap())
+    }
+
+    /// 追加・削除・更新の操作列をまとめて適用する
+    ///
+    /// 先にすべての操作を検証し、1つでも不正（JSONの形式誤り、存在しないドキュメントの削除・更新）
+    /// があればエラーを返してインデックスを変更しない
+    pub fn transaction(&mut self, ops_json: &str) -> Result<(), JsValue> {
+        let ops: Vec<TransactionOp> = serde_json::from_str(ops_json)
+            .map_err(|e| JsValue::from_str(&log_json_error(ops_json, &e)))?;
+
+        // 先行する操作を反映した存在状態で、各操作が適用できるか確認する
+        let mut present: HashMap<&str, bool> = HashMap::default();
+        for (i, op) in ops.iter().enumerate() {
+            match op {
+                TransactionOp::Add { name, .. } => {
+                    let exists = present.get(name.as_str()).copied()
+                        .unwrap_or_else(|| self.doc_aliases.contains_key(name));
+                    if exists && self.settings.append_only {
+                        return Err(JsValue::from_str(&format!("Operation {}: document already exists: {}", i, name)));
+                    }
+                    present.insert(name, true);
+                }
+                TransactionOp::Remove { name } | TransactionOp::Update { name, .. } => {
+                    let exists = present.get(name.as_str()).copied()
+                        .unwrap_or_else(|| self.doc_aliases.contains_key(name));
+                    if !exists {
+                        return Err(JsValue::from_str(&format!("Operation {}: document not found: {}", i, name)));
+                    }
+                    if matches!(op, TransactionOp::Remove { .. }) {
+                        present.insert(name, false);
+                    }
+                }
+            }
+        }
+
+        for op in ops {
+            match op {
+                TransactionOp::Add { name, aliases, name_searchable, category, meta } => {
+                    self.insert_doc(&name, aliases, name_searchable.unwrap_or(true), category, meta);
+                }
+                TransactionOp::Remove { name } => self.remove_doc(name),
+                TransactionOp::Update { name, aliases } => {
+                    self.update_doc(&name, aliases);
+                }
+            }
+        }
+
+        Ok(())
     }
 
     #[wasm_bindgen(js_name = "replaceAllDocuments")]
@@ -308,6 +1146,7 @@ impl Index {
         self.doc_aliases.clear();
         self.n_docs = 0;
         self.cache.clear();
+        self.attrs.clear();
         
         // 新しいドキュメントを追加
         self.add_documents(json)
@@ -318,23 +1157,430 @@ impl Index {
         self.doc_aliases.clear();
         self.n_docs = 0;
         self.cache.clear();
+        self.attrs.clear();
     }
     
     #[wasm_bindgen(js_name = "getVersion")]
     pub fn get_version(&self) -> u32 {
         self.version
     }
+
+    /// ローマ字からひらがなへの変換の有効・無効を切り替える（デフォルト: 有効）
+    #[wasm_bindgen(js_name = "setKanaConversion")]
+    pub fn set_kana_conversion(&mut self, enabled: bool) {
+        self.settings.kana_conversion = enabled;
+    }
+
+    /// 検索結果数の上限を設定する。これを超える件数を要求しても、エラーにはならず上限まで切り詰められる
+    ///
+    /// `None` で上限なし（デフォルト）
+    #[wasm_bindgen(js_name = "setMaxLimit")]
+    pub fn set_max_limit(&mut self, max_limit: Option<usize>) {
+        self.settings.max_limit = max_limit;
+    }
+
+    /// ローマ字からひらがなへの独自の変換を登録する（"uu" → "う" など）
+    ///
+    /// 検索時のローマ字変換で wana_kana より優先して使われ、複数が一致する場合は長いものが優先される。
+    /// 変換の区切りでのみ適用されるため、"kou" の "ou" のように音節の途中には適用されない
+    #[wasm_bindgen(js_name = "addKanaOverride")]
+    pub fn add_kana_override(&mut self, romaji: &str, hiragana: &str) -> Result<(), JsValue> {
+        let romaji = romaji.to_lowercase();
+        if romaji.is_empty() || !romaji.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(JsValue::from_str(&format!("Romaji must be ASCII letters: {}", romaji)));
+        }
+        if hiragana.is_empty() {
+            return Err(JsValue::from_str("Hiragana must not be empty"));
+        }
+        self.settings.kana_overrides.insert(romaji, hiragana.to_string());
+        Ok(())
+    }
+
+    /// 長音（ー）や小書きのかな（ゃ・っ など）の表記ゆれをまとめて照合するか設定する（デフォルト: 無効）
+    ///
+    /// "ラーメン"・"らあめん"・"ramen" が同じように照合される。情報が失われる正規化のため、
+    /// "きって" と "きつて" のように本来異なる語も区別されなくなる
+    #[wasm_bindgen(js_name = "setKanaNormalization")]
+    pub fn set_kana_normalization(&mut self, enabled: bool) {
+        self.settings.kana_normalization = enabled;
+        self.rebuild_cache();
+    }
+
+    /// すべての検索設定をデフォルトに戻す。ドキュメント・カテゴリ・メタデータ・ブースト・エイリアスの重みは変更しない
+    ///
+    /// 戻す設定: `setKanaConversion`（有効）、`setNumericExactOnly`（無効）、`setMaxLimit`（上限なし）、
+    /// `setSearchBudget`（上限なし）、`setStripChars`（なし）、`setWhitespaceMode`（"split"）、`setLocale`（"default"）、
+    /// `setAliasPositionDecay`（無効）、`setTieBreak`（"none"）、`setWordStartBonus`（0）、`setAppendOnly`（無効）、
+    /// `setKanaNormalization`（無効）、`addKanaOverride`（なし）、`setSuppressPartialsOnExact`（無効）、
+    /// `setPreferExactCase`（無効）
+    #[wasm_bindgen(js_name = "resetAllSettings")]
+    pub fn reset_all_settings(&mut self) {
+        self.settings = IndexSettings::default();
+        self.rebuild_cache();
+    }
+
+    /// ドキュメントを含まない検索設定だけを書き出す（別のインデックスへ同じ調整を適用するため）
+    #[wasm_bindgen(js_name = "exportConfig")]
+    pub fn export_config(&self) -> JsValue {
+        self.settings.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).unwrap()
+    }
+
+    /// `exportConfig` で書き出した設定を読み込む。ドキュメントやブーストなどは変更しない
+    ///
+    /// 含まれない項目はデフォルト値になる。不正な値があれば何も変更せずにエラーにする
+    #[wasm_bindgen(js_name = "importConfig")]
+    pub fn import_config(&mut self, config: JsValue) -> Result<(), JsValue> {
+        let settings: IndexSettings = serde_wasm_bindgen::from_value(config)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        settings.validate().map_err(|e| JsValue::from_str(&e))?;
+        self.settings = settings;
+        self.rebuild_cache();
+        Ok(())
+    }
+
+    /// 1回の検索で調べるドキュメント数の上限を設定する。上限に達すると、それまでに見つかった結果だけを返す
+    ///
+    /// 結果が不完全になる代わりに、広すぎるクエリでも処理時間が一定に収まる。`None` で上限なし（デフォルト）
+    #[wasm_bindgen(js_name = "setSearchBudget")]
+    pub fn set_search_budget(&mut self, max_ops: Option<usize>) {
+        self.settings.search_budget = max_ops;
+    }
+
+    /// 照合前に名前・エイリアス・クエリから取り除く文字を設定する（デフォルト: なし）
+    ///
+    /// `":"` を指定すると `:smile:` と `smile` が同じように照合される。ドキュメントIDは元のまま
+    #[wasm_bindgen(js_name = "setStripChars")]
+    pub fn set_strip_chars(&mut self, chars: &str) {
+        self.settings.strip_chars = chars.to_string();
+        self.rebuild_cache();
+    }
+
+    /// クエリ内の空白の扱いを設定する（"split" | "strip" | "keep"、デフォルト: "split"）
+    ///
+    /// - split: 空白で区切ってAND検索する
+    /// - strip: 名前・エイリアス・クエリから空白を取り除いて照合する
+    /// - keep: 空白も文字としてそのまま照合する
+    #[wasm_bindgen(js_name = "setWhitespaceMode")]
+    pub fn set_whitespace_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        let mode = WhitespaceMode::parse(mode)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown whitespace mode: {}", mode)))?;
+        self.settings.whitespace_mode = mode;
+        self.rebuild_cache();
+        Ok(())
+    }
+
+    /// 大文字・小文字の変換に使うロケールを設定する（"default" | "tr" | "de"、デフォルト: "default"）
+    ///
+    /// "tr" では `I` を `ı`、`İ` を `i` に、"de" では `ß` を `ss` に変換する
+    #[wasm_bindgen(js_name = "setLocale")]
+    pub fn set_locale(&mut self, locale: &str) -> Result<(), JsValue> {
+        let locale = Locale::parse(locale)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown locale: {}", locale)))?;
+        self.settings.locale = locale;
+        self.rebuild_cache();
+        Ok(())
+    }
+
+    /// 同じ種類の一致の中で、前に並んでいるエイリアスでの一致を上位にする（デフォルト: 無効）
+    #[wasm_bindgen(js_name = "setAliasPositionDecay")]
+    pub fn set_alias_position_decay(&mut self, enabled: bool) {
+        self.settings.alias_position_decay = enabled;
+    }
+
+    /// 名前・エイリアスの完全一致する結果があるとき、部分一致の結果を除く（デフォルト: 無効）
+    ///
+    /// 前方一致の結果は残す。完全一致がなければ結果は変わらない
+    #[wasm_bindgen(js_name = "setSuppressPartialsOnExact")]
+    pub fn set_suppress_partials_on_exact(&mut self, enabled: bool) {
+        self.settings.suppress_partials_on_exact = enabled;
+    }
+
+    /// 完全一致した結果のうち、大文字・小文字まで検索語と一致するものを上位にする（デフォルト: 無効）
+    ///
+    /// "Cat" で検索したとき、名前が "Cat" のドキュメントを "cat" のドキュメントより上位にする
+    #[wasm_bindgen(js_name = "setPreferExactCase")]
+    pub fn set_prefer_exact_case(&mut self, enabled: bool) {
+        self.settings.prefer_exact_case = enabled;
+    }
+
+    /// 最終的に同順位となった結果の並べ方を設定する（"none" | "lexicographic" | "insertion" | "aliasCount"、デフォルト: "none"）
+    ///
+    /// "insertion" では先に追加されたドキュメントが上位になる。`updateDocument` では順序は変わらない
+    #[wasm_bindgen(js_name = "setTieBreak")]
+    pub fn set_tie_break(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.settings.tie_break = TieBreak::parse(mode)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown tie break mode: {}", mode)))?;
+        Ok(())
+    }
+
+    /// 数字のみのクエリ（"100" など）を名前・エイリアスの完全一致だけで照合する（デフォルト: 無効）
+    #[wasm_bindgen(js_name = "setNumericExactOnly")]
+    pub fn set_numeric_exact_only(&mut self, enabled: bool) {
+        self.settings.numeric_exact_only = enabled;
+    }
+    
+    /// 検索結果に寄与した名前・エイリアスの集計を開始する
+    ///
+    /// 集計結果はメモリ上のみに保持され、`dump()` には含まれない
+    #[wasm_bindgen(js_name = "enableMatchStats")]
+    pub fn enable_match_stats(&mut self) {
+        self.match_stats.get_or_insert_with(HashMap::default);
+    }
+
+    /// ヒット数の多い名前・エイリアスを上位から返す
+    #[wasm_bindgen(js_name = "matchStats")]
+    pub fn match_stats(&self, limit: Option<usize>) -> Result<JsValue, JsValue> {
+        let mut stats: Vec<TokenHits> = self.match_stats.iter()
+            .flatten()
+            .map(|(token, hits)| TokenHits { token: token.clone(), hits: *hits })
+            .collect();
+        stats.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.token.cmp(&b.token)));
+        stats.truncate(limit.unwrap_or(10));
+        Ok(serde_wasm_bindgen::to_value(&stats).unwrap())
+    }
+
+    /// 集計結果をリセットする（集計は有効なまま）
+    #[wasm_bindgen(js_name = "resetMatchStats")]
+    pub fn reset_match_stats(&mut self) {
+        if let Some(stats) = &mut self.match_stats {
+            stats.clear();
+        }
+    }
     
     // 内部メソッド（非公開）
+
+    /// バイト列をインデックスとして読み込む（旧バージョンの形式はマイグレーションする）
+    ///
+    /// どの形式もバイト列を最後まで使い切った場合だけ読み込めたとみなす
+    fn deserialize_any_version(bytes: &[u8]) -> Result<Index, JsValue> {
+        // まず新しい形式で読み込みを試みる
+        if let Ok(index) = decode_exact::<Index>(bytes) {
+            return Ok(index);
+        }
+
+        // 失敗したらバージョン3の形式として読み込みを試みる
+        if let Ok(v3_index) = decode_exact::<IndexV3>(bytes) {
+            return Ok(Index {
+                doc_aliases: v3_index.doc_aliases,
+                n_docs: v3_index.n_docs,
+                settings: v3_index.settings,
+                ..Index::new()
+            });
+        }
+
+        // 失敗したらバージョン2の形式として読み込みを試みる
+        if let Ok(v2_index) = decode_exact::<IndexV2>(bytes) {
+            return Ok(Index {
+                doc_aliases: v2_index.doc_aliases,
+                n_docs: v2_index.n_docs,
+                ..Index::new()
+            });
+        }
+
+        // さらに失敗したら旧形式として読み込みを試みる
+        match decode_exact::<OldIndex>(bytes) {
+            // 旧形式から新形式へマイグレーション
+            Ok(old_index) => Ok(Index {
+                doc_aliases: old_index.doc_aliases.into_iter()
+                    .map(|(k, v)| {
+                        (Arc::new(k), v.into_iter().map(Arc::new).collect())
+                    })
+                    .collect(),
+                n_docs: old_index.n_docs,
+                ..Index::new()
+            }),
+            Err(e) => Err(JsValue::from_str(&format!(
+                "Failed to load index: {}. The index format may be incompatible.",
+                e
+            )))
+        }
+    }
+
+    /// ヘッダーのない旧形式のバイト列をインデックスとして読み込む
+    ///
+    /// ヘッダーもチェックサムもないため、ドキュメントが1件もない、または保存された件数と
+    /// 実際のドキュメント数が食い違う結果は、無関係なバイト列を読み違えたものとして扱う
+    fn deserialize_legacy(bytes: &[u8]) -> Option<Index> {
+        Self::deserialize_any_version(bytes).ok()
+            .filter(|index| !index.doc_aliases.is_empty() && index.n_docs == index.doc_aliases.len())
+    }
+
+    /// ドキュメントをまとめて追加し、キャッシュを再構築する
+    fn index_docs(&mut self, docs: Vec<Doc>) {
+        let emoji_count = docs.len();
+        
+        // 事前確保
+        if self.doc_aliases.is_empty() {
+            self.doc_aliases = HashMap::with_capacity_and_hasher(emoji_count, Default::default());
+        }
+        
+        for doc in docs {
+            let doc_name = Arc::new(doc.name);
+            let aliases: Vec<Arc<String>> = self.dedup_aliases(doc.aliases).into_iter()
+                .map(Arc::new)
+                .collect();
+            
+            if self.doc_aliases.contains_key(&doc_name) { 
+                self.remove_doc(doc_name.as_ref().clone()); 
+            }
+            
+            self.attrs.set_name_searchable(&doc_name, doc.name_searchable.unwrap_or(true));
+            self.attrs.set_category(&doc_name, doc.category);
+            self.attrs.set_meta(&doc_name, doc.meta);
+            self.attrs.assign_seq(&doc_name);
+            self.doc_aliases.insert(Arc::clone(&doc_name), aliases);
+            self.n_docs += 1;
+        }
+        
+        debug_assert_eq!(self.n_docs, self.doc_aliases.len());
+        
+        // キャッシュを再構築
+        self.rebuild_cache();
+    }
+
+    /// 追記専用モードで、追加しようとしている名前が既存のドキュメントや同じ一括追加内の名前と重複していればエラーにする
+    fn check_append_only<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> Result<(), JsValue> {
+        if !self.settings.append_only {
+            return Ok(());
+        }
+        let mut seen = HashSet::default();
+        for name in names {
+            if !seen.insert(name) || self.doc_aliases.contains_key(&Arc::new(name.to_string())) {
+                return Err(JsValue::from_str(&format!("Document already exists: {}", name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 大文字・小文字、全角・半角、カタカナ・ひらがなの違いだけのエイリアスを1つにまとめる（最初のものを残す）
+    fn dedup_aliases(&self, aliases: Vec<String>) -> Vec<String> {
+        let mut seen = HashSet::default();
+        aliases.into_iter()
+            .filter(|alias| seen.insert(self.cache.key(alias)))
+            .collect()
+    }
+
+    /// ドキュメントを追加（既存のドキュメントは置き換える）
+    fn insert_doc(&mut self, name: &str, aliases: Vec<String>, name_searchable: bool, category: Option<String>, meta: Option<serde_json::Value>) {
+        let doc_name = Arc::new(name.to_string());
+        let arc_aliases: Vec<Arc<String>> = self.dedup_aliases(aliases).into_iter()
+            .map(Arc::new)
+            .collect();
+        
+        // 既存のドキュメントなら削除
+        if self.doc_aliases.contains_key(&doc_name) { 
+            self.remove_doc(name.to_string()); 
+        }
+        
+        // キャッシュを更新
+        self.update_cache_for_document(&doc_name, &arc_aliases);
+        
+        self.attrs.set_name_searchable(name, name_searchable);
+        self.attrs.set_category(name, category);
+        self.attrs.set_meta(name, meta);
+        self.attrs.assign_seq(name);
+        self.doc_aliases.insert(doc_name, arc_aliases);
+        self.n_docs += 1;
+        debug_assert_eq!(self.n_docs, self.doc_aliases.len());
+    }
+
+    /// 既存ドキュメントのエイリアスを置き換える。存在しなければ false
+    fn update_doc(&mut self, doc_id: &str, aliases: Vec<String>) -> bool {
+        if !self.doc_aliases.contains_key(&Arc::new(doc_id.to_string())) {
+            return false;
+        }
+        
+        // 名前の検索可否、カテゴリ、メタデータ、追加された順序、エイリアスの重みは引き継ぐ
+        let name_searchable = self.attrs.is_name_searchable(doc_id);
+        let category = self.attrs.category(doc_id).map(str::to_string);
+        let meta = self.attrs.meta(doc_id).cloned();
+        let seq = self.attrs.seq(doc_id);
+        let mut weights = self.attrs.alias_weights.get(doc_id).cloned().unwrap_or_default();
+        
+        // アップデート前のドキュメントを削除
+        self.remove_doc(doc_id.to_string());
+        
+        // 新しいドキュメントを追加
+        self.insert_doc(doc_id, aliases, name_searchable, category, meta);
+        self.attrs.doc_seq.insert(doc_id.to_string(), seq);
+        
+        // 新しいエイリアスに残ったものだけ重みを戻す
+        let kept = &self.doc_aliases[&Arc::new(doc_id.to_string())];
+        weights.retain(|alias, _| kept.iter().any(|a| a.as_str() == alias.as_str()));
+        self.attrs.set_alias_weights(doc_id, weights);
+        
+        true
+    }
+
+    /// クエリのJSON配列を読み込み、照合に使う検索語と小文字化する前の検索語を返す
+    ///
+    /// 小文字化する前の検索語は大文字・小文字の一致を優先する場合のみ作る（それ以外は空）
+    fn parse_queries(&self, query_json: &str) -> Result<(Vec<String>, Vec<String>), JsValue> {
+        let queries = parse_queries(query_json, &self.settings)?;
+        if !self.settings.prefer_exact_case {
+            return Ok((queries, Vec::new()));
+        }
+        let original: Vec<String> = serde_json::from_str(query_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let removed = self.settings.removed_chars();
+        let cased = original.iter().map(|q| strip(q, &removed)).collect();
+        Ok((queries, cased))
+    }
+
+    /// 検索を実行し、集計が有効ならヒットした名前・エイリアスを数える
+    fn ranked(&mut self, queries: &[String], cased_queries: &[String], limit: usize) -> Vec<Candidate> {
+        let results = self.engine(cased_queries).search_ranked(queries, limit);
+        self.record_matches(&results);
+        results
+    }
+
+    /// 集計が有効なら、返す結果にヒットした名前・エイリアスを数える
+    fn record_matches(&mut self, results: &[Candidate]) {
+        self.record_tokens(results.iter().map(|candidate| &candidate.matched));
+    }
+
+    /// 集計が有効なら、一致した名前・エイリアスを数える（一致の種類を持たない検索用）
+    fn record_tokens<'t>(&mut self, matched: impl Iterator<Item = &'t Arc<String>>) {
+        if let Some(stats) = &mut self.match_stats {
+            for token in matched {
+                *stats.entry((**token).clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// 要求された結果数に上限を適用する（未指定なら10件）
+    fn result_limit(&self, limit: Option<usize>) -> usize {
+        let limit = limit.unwrap_or(10);
+        match self.settings.max_limit {
+            Some(max_limit) => limit.min(max_limit),
+            None => limit,
+        }
+    }
+
+    /// 現在の設定で検索エンジンを初期化（`cased_queries` は `parse_queries` が返した小文字化する前の検索語）
+    fn engine<'a>(&'a mut self, cased_queries: &'a [String]) -> SearchEngine<'a> {
+        SearchEngine {
+            doc_aliases: &self.doc_aliases,
+            cache: &mut self.cache,
+            settings: &self.settings,
+            attrs: &self.attrs,
+            fields: SearchFields::default(),
+            cased_queries,
+        }
+    }
     
     /// キャッシュを再構築
     fn rebuild_cache(&mut self) {
         self.cache.clear();
+        self.cache.strip_chars = self.settings.removed_chars();
+        self.cache.locale = self.settings.locale;
+        self.cache.kana_normalization = self.settings.kana_normalization;
         
         for (doc_name, aliases) in &self.doc_aliases {
-            // ドキュメント名のキャッシュを構築
+            // ドキュメント名のキャッシュと逆引きインデックスを構築
             self.cache.get_lowercase(doc_name);
             self.cache.get_hiragana(doc_name);
+            self.cache.add_name_mapping(Arc::clone(doc_name));
             
             // エイリアスのキャッシュと逆引きインデックスを構築
             for alias in aliases {
@@ -347,9 +1593,10 @@ impl Index {
     
     /// 単一ドキュメントのキャッシュを更新
     fn update_cache_for_document(&mut self, doc_name: &Arc<String>, aliases: &[Arc<String>]) {
-        // ドキュメント名のキャッシュを追加
+        // ドキュメント名のキャッシュと逆引きインデックスを追加
         self.cache.get_lowercase(doc_name);
         self.cache.get_hiragana(doc_name);
+        self.cache.add_name_mapping(Arc::clone(doc_name));
         
         // エイリアスのキャッシュと逆引きインデックスを追加
         for alias in aliases {
@@ -0,0 +1,102 @@
+use wana_kana::{ConvertJapanese, IsJapaneseChar};
+
+use crate::settings::Locale;
+
+/// 1文字を小文字化して追加する（ロケール固有の変換を含む）
+///
+/// 語末のシグマ ς も σ に揃えて、位置によらず同じ文字列になるようにする
+fn push_lowercase(out: &mut String, c: char, locale: Locale) {
+    match (locale, c) {
+        (Locale::Turkish, 'I') => out.push('ı'),
+        (Locale::Turkish, 'İ') => out.push('i'),
+        (Locale::German, 'ß' | 'ẞ') => out.push_str("ss"),
+        (_, 'ς') => out.push('σ'),
+        _ => out.extend(c.to_lowercase()),
+    }
+}
+
+/// 大文字・小文字を区別しない比較用に小文字化する
+pub fn lowercase(text: &str, locale: Locale) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_lowercase(&mut out, c, locale);
+    }
+    out
+}
+
+/// 完全一致の比較用に文字列を正規化する
+///
+/// 大文字・小文字、全角・半角英数字、カタカナ・ひらがなを同一視する
+pub fn fold(text: &str, locale: Locale) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        let c = match c {
+            // 全角英数字・記号を半角に
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            // カタカナをひらがなに
+            '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        };
+        push_lowercase(&mut out, c, locale);
+    }
+    out
+}
+
+/// 長音や小書きのかなの表記ゆれをまとめる（情報が失われる正規化）
+///
+/// カタカナをひらがなにし、小書きのかな（ゃ・っ など）を通常のかなに、長音符（ー）と
+/// 直前のかなと同じ母音のかな（らあ の あ）を取り除く。"ラーメン"・"らあめん"・"らめん" は同じになる
+pub fn normalize_kana(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev_vowel: Option<char> = None;
+    for c in text.chars() {
+        let c = match c {
+            // カタカナをひらがなに
+            '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        };
+        let c = match c {
+            'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'っ' | 'ゃ' | 'ゅ' | 'ょ' | 'ゎ' => {
+                char::from_u32(c as u32 + 1).unwrap_or(c)
+            }
+            'ゕ' => 'か',
+            'ゖ' => 'け',
+            _ => c,
+        };
+        if c == 'ー' {
+            continue;
+        }
+        let vowel = kana_vowel(c);
+        if vowel.is_some() && vowel == prev_vowel && matches!(c, 'あ' | 'い' | 'う' | 'え' | 'お') {
+            continue;
+        }
+        prev_vowel = vowel;
+        out.push(c);
+    }
+    out
+}
+
+/// ひらがな1文字の母音（ローマ字表記の末尾）
+fn kana_vowel(c: char) -> Option<char> {
+    if !c.is_hiragana() {
+        return None;
+    }
+    c.to_string().to_romaji().chars().last().filter(|v| "aiueo".contains(*v))
+}
+
+/// 照合前に、指定された文字（`:smile:` の `:` など）を取り除く
+pub fn strip(text: &str, chars: &str) -> String {
+    if chars.is_empty() {
+        return text.to_string();
+    }
+    text.chars().filter(|c| !chars.contains(*c)).collect()
+}
+
+/// 表示用にローマ字へ変換する（かなを含まない文字列はそのまま返す）
+pub fn romaji(text: &str) -> String {
+    if text.chars().any(|c| c.is_kana()) {
+        text.to_romaji()
+    } else {
+        text.to_string()
+    }
+}
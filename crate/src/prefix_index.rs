@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use crate::cache::StringCache;
+use crate::DocAliases;
+
+/// 前方一致検索用の索引の1項目
+pub struct PrefixEntry {
+    /// 照合に使う小文字化済みの名前またはエイリアス
+    pub key: Arc<String>,
+    pub doc_name: Arc<String>,
+    /// 元の名前またはエイリアス
+    pub matched: Arc<String>,
+    /// 名前の項目か（false ならエイリアス）
+    pub is_name: bool,
+}
+
+/// 名前・エイリアスをキーの辞書順に並べた前方一致検索用の索引
+///
+/// 同じ前方一致を持つ項目は連続して並ぶため、二分探索で範囲を求めるだけで全件を走査せずに済む
+pub struct PrefixIndex {
+    entries: Vec<PrefixEntry>,
+}
+
+impl PrefixIndex {
+    /// すべてのドキュメントの名前・エイリアスから索引を作る
+    pub fn build(doc_aliases: &DocAliases, cache: &mut StringCache) -> Self {
+        let mut entries = Vec::with_capacity(doc_aliases.len());
+        for (doc_name, aliases) in doc_aliases {
+            entries.push(PrefixEntry {
+                key: cache.get_lowercase(doc_name),
+                doc_name: Arc::clone(doc_name),
+                matched: Arc::clone(doc_name),
+                is_name: true,
+            });
+            for alias in aliases {
+                entries.push(PrefixEntry {
+                    key: cache.get_lowercase(alias),
+                    doc_name: Arc::clone(doc_name),
+                    matched: Arc::clone(alias),
+                    is_name: false,
+                });
+            }
+        }
+        entries.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        PrefixIndex { entries }
+    }
+
+    /// キーが `prefix` で始まる項目
+    pub fn with_prefix(&self, prefix: &str) -> &[PrefixEntry] {
+        let start = self.entries.partition_point(|entry| entry.key.as_str() < prefix);
+        let len = self.entries[start..].partition_point(|entry| entry.key.starts_with(prefix));
+        &self.entries[start..start + len]
+    }
+}
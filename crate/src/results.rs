@@ -0,0 +1,112 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::cache::MatchPriority;
+
+// JSに返す結果の構造体は、キーをcamelCaseにそろえるため `#[serde(rename_all = "camelCase")]` を付ける
+
+/// 一致の種類を表示名で付けた検索結果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabeledResult {
+    pub id: String,
+    pub priority: &'static str,
+}
+
+/// 名前・エイリアスごとの検索ヒット数
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenHits {
+    pub token: String,
+    pub hits: u64,
+}
+
+/// 2-gramの重なり具合による検索結果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlapResult {
+    pub id: String,
+    pub overlap: f64,
+}
+
+/// ドキュメント名のローマ字表記を付けた検索結果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RomajiResult {
+    pub id: String,
+    pub romaji: String,
+}
+
+/// ドキュメントのカテゴリを付けた検索結果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorizedResult {
+    pub id: String,
+    pub category: Option<String>,
+}
+
+/// 検索語に一致した範囲（UTF-16 オフセットの `[start, end)`）を付けた検索結果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightResult {
+    pub id: String,
+    pub name_ranges: Vec<[usize; 2]>,
+    /// 一致したエイリアス（名前での一致なら `None`）
+    pub matched_alias: Option<String>,
+    pub alias_ranges: Vec<[usize; 2]>,
+}
+
+/// 一致の種類ごとの件数
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchDistribution {
+    pub name_exact: usize,
+    pub alias_exact: usize,
+    pub name_prefix: usize,
+    pub alias_prefix: usize,
+    pub name_partial: usize,
+    pub alias_partial: usize,
+}
+
+impl MatchDistribution {
+    /// 一致の種類に対応する件数を1つ増やす（あいまい一致は通常の検索では発生しないため数えない）
+    pub fn record(&mut self, priority: MatchPriority) {
+        let count = match priority {
+            MatchPriority::NameExact => &mut self.name_exact,
+            MatchPriority::AliasExact => &mut self.alias_exact,
+            MatchPriority::NamePrefix => &mut self.name_prefix,
+            MatchPriority::AliasPrefix => &mut self.alias_prefix,
+            MatchPriority::NamePartial => &mut self.name_partial,
+            MatchPriority::AliasPartial => &mut self.alias_partial,
+            MatchPriority::NameFuzzyPrefix | MatchPriority::AliasFuzzyPrefix => return,
+        };
+        *count += 1;
+    }
+}
+
+/// TypeScriptから型付きで扱える検索結果
+#[wasm_bindgen]
+pub struct SearchResult {
+    id: String,
+    priority: MatchPriority,
+}
+
+impl SearchResult {
+    pub fn new(id: String, priority: MatchPriority) -> Self {
+        SearchResult { id, priority }
+    }
+}
+
+#[wasm_bindgen]
+impl SearchResult {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// 一致の種類（"name_exact", "alias_prefix" など）
+    #[wasm_bindgen(getter, js_name = "matchType")]
+    pub fn match_type(&self) -> String {
+        self.priority.label().to_string()
+    }
+}
@@ -1,180 +1,1038 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use wana_kana::ConvertJapanese;
 
 use crate::cache::{StringCache, MatchPriority};
+use crate::doc_attrs::DocAttributes;
+use crate::fuzzy::{bigram_overlap, edit_distance, prefix_distance};
+use crate::highlight::find_ranges;
+use crate::normalize::{lowercase, normalize_kana, strip};
+use crate::prefix_index::PrefixIndex;
+use crate::settings::{IndexSettings, TieBreak, WhitespaceMode};
+
+/// あいまい前方一致で許容する最大編集距離
+pub const MAX_FUZZY_DISTANCE: usize = 1;
+/// あいまい前方一致を行うクエリの最小文字数
+pub const MIN_FUZZY_QUERY_CHARS: usize = 3;
+/// 検索語の候補として提案する語の最大編集距離
+pub const MAX_SUGGEST_DISTANCE: usize = 2;
+/// 単語の区切りとみなす文字（この直後から始まる一致は単語の先頭での一致になる）
+pub const WORD_DELIMITERS: &[char] = &['_', '-', ' ', '.'];
 
 /// 検索エンジンの実装
 pub struct SearchEngine<'a> {
     pub doc_aliases: &'a HashMap<Arc<String>, Vec<Arc<String>>>,
     pub cache: &'a mut StringCache,
+    pub settings: &'a IndexSettings,
+    pub attrs: &'a DocAttributes,
+    /// 照合の対象にするフィールド
+    pub fields: SearchFields,
+    /// 小文字化する前の検索語（大文字・小文字の一致を優先する場合のみ）
+    pub cased_queries: &'a [String],
+}
+
+/// 照合の対象にするフィールド（名前・エイリアス）
+#[derive(Debug, Clone, Copy)]
+pub struct SearchFields {
+    pub name: bool,
+    pub alias: bool,
+}
+
+impl Default for SearchFields {
+    fn default() -> Self {
+        SearchFields { name: true, alias: true }
+    }
+}
+
+impl SearchFields {
+    /// `["name", "alias"]` のようなフィールド名の一覧から作る（空なら両方）
+    pub fn parse(fields: &[String]) -> Result<Self, String> {
+        if fields.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut selected = SearchFields { name: false, alias: false };
+        for field in fields {
+            match field.as_str() {
+                "name" => selected.name = true,
+                "alias" => selected.alias = true,
+                _ => return Err(format!("Unknown search field: {}", field)),
+            }
+        }
+        Ok(selected)
+    }
+}
+
+/// 検索語と、そのひらがな変換結果
+struct QueryTerm<'q> {
+    text: &'q str,
+    hiragana: Option<String>,
+    /// 完全一致のみを許可する（数字のみのクエリなど）
+    exact_only: bool,
+}
+
+impl<'q> QueryTerm<'q> {
+    fn new(text: &'q str, settings: &IndexSettings) -> Self {
+        let exact_only = settings.numeric_exact_only
+            && !text.is_empty()
+            && text.bytes().all(|b| b.is_ascii_digit());
+        let hiragana = if settings.kana_conversion && !exact_only {
+            romaji_to_hiragana(text, &settings.kana_overrides)
+                .map(|h| if settings.kana_normalization { normalize_kana(&h) } else { h })
+        } else {
+            None
+        };
+        QueryTerm { text, hiragana, exact_only }
+    }
+
+    fn is_exact(&self, target: &str) -> bool {
+        target == self.text || self.hiragana.as_deref() == Some(target)
+    }
+
+    fn is_prefix_of(&self, target: &str) -> bool {
+        if self.exact_only {
+            return false;
+        }
+        target.starts_with(self.text)
+            || self.hiragana.as_deref().is_some_and(|h| target.starts_with(h))
+    }
+
+    /// 部分一致（ひらがな変換含む）
+    fn is_partial_of(&self, target: &str, target_hiragana: Option<&str>) -> bool {
+        if self.exact_only {
+            return false;
+        }
+        target.contains(self.text)
+            || self.hiragana.as_deref().is_some_and(|h| {
+                target.contains(h) || target_hiragana.is_some_and(|th| th.contains(h))
+            })
+    }
+
+    /// あいまい前方一致の判定（ひらがな変換含む）
+    fn is_fuzzy_prefix_of(&self, target: &str, max_distance: usize) -> bool {
+        if self.exact_only {
+            return false;
+        }
+        let matches = |query: &str| {
+            let chars: Vec<char> = query.chars().collect();
+            chars.len() >= MIN_FUZZY_QUERY_CHARS
+                && prefix_distance(&chars, target, max_distance).is_some()
+        };
+        matches(self.text) || self.hiragana.as_deref().is_some_and(matches)
+    }
+
+    /// AND検索用の包含判定
+    fn is_found_in(&self, target: &str, target_hiragana: Option<&str>) -> bool {
+        if self.exact_only {
+            return self.is_exact(target);
+        }
+        target.contains(self.text)
+            || self.hiragana.as_deref()
+                .zip(target_hiragana)
+                .is_some_and(|(h, th)| th.contains(h))
+    }
+}
+
+/// ローマ字をひらがなに変換し、変換しきれないものは捨てる
+///
+/// 変換後にアルファベットが残るもの（`xqz`、`kitty` → `きtty` など）や、
+/// ローマ字に戻して再変換すると同じひらがなにならないものはノイズになるため `None` を返す。
+/// `si` と `shi` のような表記ゆれは同じひらがなに戻るので有効とみなす。
+fn valid_hiragana_conversion(romaji: &str) -> Option<String> {
+    let hiragana = romaji.to_hiragana();
+    if hiragana.chars().any(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    if hiragana.to_romaji().to_hiragana() != hiragana {
+        return None;
+    }
+    Some(hiragana)
+}
+
+/// 独自の変換（`overrides`）を優先してローマ字をひらがなに変換する
+///
+/// 変換の区切り（それまでのローマ字がひらがなに変換しきれる位置）で、最も長く一致する独自の変換を使い、
+/// 残りは wana_kana で変換する。独自の変換を1つも使わない場合は `valid_hiragana_conversion` と同じ
+fn romaji_to_hiragana(romaji: &str, overrides: &BTreeMap<String, String>) -> Option<String> {
+    if overrides.is_empty() {
+        return valid_hiragana_conversion(romaji);
+    }
+    let converts = |pending: &str| !pending.to_hiragana().chars().any(|c| c.is_ascii_alphabetic());
+
+    let mut hiragana = String::new();
+    let mut pending = String::new();
+    let mut applied = false;
+    let mut rest = romaji;
+    while let Some(c) = rest.chars().next() {
+        if converts(&pending) {
+            let longest = overrides.iter()
+                .filter(|(from, _)| rest.starts_with(from.as_str()))
+                .max_by_key(|(from, _)| from.len());
+            if let Some((from, to)) = longest {
+                hiragana.push_str(&pending.to_hiragana());
+                hiragana.push_str(to);
+                pending.clear();
+                rest = &rest[from.len()..];
+                applied = true;
+                continue;
+            }
+        }
+        pending.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if !applied {
+        return valid_hiragana_conversion(romaji);
+    }
+    hiragana.push_str(&pending.to_hiragana());
+    if hiragana.chars().any(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(hiragana)
+}
+
+/// スペース区切りの単一クエリはAND検索のキーワードに分割する（空白を区切りとして扱う設定の場合のみ）
+fn and_keywords<'q>(queries: &'q [String], settings: &IndexSettings) -> Option<Vec<&'q str>> {
+    if settings.whitespace_mode != WhitespaceMode::Split {
+        return None;
+    }
+    match queries {
+        [query] if query.contains(' ') => Some(query.split(' ').collect()),
+        _ => None,
+    }
+}
+
+/// 検索に一致したドキュメント
+#[derive(Clone)]
+pub struct Candidate {
+    pub priority: MatchPriority,
+    pub doc_name: Arc<String>,
+    /// 最良の一致をもたらした名前またはエイリアス
+    pub matched: Arc<String>,
+}
+
+/// より高い優先度であれば更新する
+fn consider(best: &mut Option<(MatchPriority, Arc<String>)>, priority: MatchPriority, matched: &Arc<String>) {
+    if best.as_ref().is_none_or(|(p, _)| *p > priority) {
+        *best = Some((priority, Arc::clone(matched)));
+    }
+}
+
+/// `term` が `text` の先頭、または区切り文字の直後から一致するか
+fn starts_word(text: &str, term: &str) -> bool {
+    text.match_indices(term)
+        .any(|(start, _)| start == 0 || text[..start].ends_with(WORD_DELIMITERS))
 }
 
 impl<'a> SearchEngine<'a> {
+    /// 名前を照合の対象にするか
+    fn name_searchable(&self, doc_name: &str) -> bool {
+        self.fields.name && self.attrs.is_name_searchable(doc_name)
+    }
 
+    /// 照合の対象にするエイリアス（エイリアスを対象にしない場合は空）
+    fn searched_aliases<'b>(&self, aliases: &'b [Arc<String>]) -> &'b [Arc<String>] {
+        if self.fields.alias { aliases } else { &[] }
+    }
+
+    fn hiragana_of(&mut self, text: &Arc<String>) -> Option<Arc<String>> {
+        if self.settings.kana_conversion {
+            self.cache.get_hiragana(text)
+        } else {
+            None
+        }
+    }
 
     /// AND検索の実装
-    pub fn search_and(&mut self, keywords: Vec<&str>, limit: usize) -> Vec<String> {
-        let mut matches = Vec::with_capacity(limit);
-        let mut seen = HashSet::with_capacity_and_hasher(limit, Default::default());
+    ///
+    /// 名前だけですべてのキーワードを含むものは NamePartial、エイリアスも必要なものは AliasPartial とする
+    ///
+    /// 調べるドキュメントは2回の走査を合わせて最大 `budget` 件
+    pub fn search_and(&mut self, keywords: Vec<&str>, limit: usize, budget: usize) -> Vec<Candidate> {
+        let terms: Vec<QueryTerm> = keywords.into_iter()
+            .map(|keyword| QueryTerm::new(keyword, self.settings))
+            .collect();
+        let capacity = limit.min(self.doc_aliases.len());
+        let mut matches = Vec::with_capacity(capacity);
+        let mut seen = HashSet::with_capacity_and_hasher(capacity, Default::default());
+
+        let doc_aliases = self.doc_aliases;
 
         // 名前にすべてのキーワードが含まれている
-        for (doc_name, _) in self.doc_aliases.iter() {
+        for doc_name in doc_aliases.keys().take(budget) {
+            if !self.name_searchable(doc_name) {
+                continue;
+            }
+
             let doc_name_lower = self.cache.get_lowercase(doc_name);
-            let doc_name_hiragana = self.cache.get_hiragana(doc_name);
-            
-            if keywords.iter().all(|keyword| {
-                doc_name_lower.contains(keyword) || 
-                doc_name_hiragana.as_ref()
-                    .map_or(false, |h| h.contains(&keyword.to_hiragana()))
-            }) {
-                if seen.insert(Arc::clone(doc_name)) {
-                    matches.push(Arc::clone(doc_name));
-                    if matches.len() >= limit {
-                        return matches.into_iter().map(|arc| (*arc).clone()).collect();
-                    }
+            let doc_name_hiragana = self.hiragana_of(doc_name);
+
+            if terms.iter().all(|term| term.is_found_in(&doc_name_lower, doc_name_hiragana.as_deref().map(String::as_str)))
+                && seen.insert(Arc::clone(doc_name))
+            {
+                matches.push(Candidate {
+                    priority: MatchPriority::NamePartial,
+                    doc_name: Arc::clone(doc_name),
+                    matched: Arc::clone(doc_name),
+                });
+                if matches.len() >= limit {
+                    return matches;
                 }
             }
         }
-        
+
         // 名前またはエイリアスにすべてのキーワードが含まれている
-        for (doc_name, aliases) in self.doc_aliases.iter() {
+        let remaining = budget.saturating_sub(doc_aliases.len());
+        for (doc_name, aliases) in doc_aliases.iter().take(remaining) {
             if seen.contains(doc_name) {
                 continue;
             }
 
+            let aliases = self.searched_aliases(aliases);
+            let name_searchable = self.name_searchable(doc_name);
             let doc_name_lower = self.cache.get_lowercase(doc_name);
-            let doc_name_hiragana = self.cache.get_hiragana(doc_name);
-            
-            if keywords.iter().all(|keyword| {
+            let doc_name_hiragana = self.hiragana_of(doc_name);
+            let alias_forms: Vec<(Arc<String>, Option<Arc<String>>)> = aliases.iter()
+                .map(|alias| (self.cache.get_lowercase(alias), self.hiragana_of(alias)))
+                .collect();
+
+            let all_found = terms.iter().all(|term| {
                 // 名前のチェック
-                if doc_name_lower.contains(keyword) || 
-                   doc_name_hiragana.as_ref()
-                       .map_or(false, |h| h.contains(&keyword.to_hiragana())) {
-                    return true;
-                }
-                
-                // エイリアスのチェック
-                aliases.iter().any(|alias| {
-                    let alias_lower = self.cache.get_lowercase(alias);
-                    let alias_hiragana = self.cache.get_hiragana(alias);
-                    
-                    alias_lower.contains(keyword) || 
-                    alias_hiragana.as_ref()
-                        .map_or(false, |h| h.contains(&keyword.to_hiragana()))
-                })
-            }) {
-                if seen.insert(Arc::clone(doc_name)) {
-                    matches.push(Arc::clone(doc_name));
-                    if matches.len() >= limit {
-                        return matches.into_iter().map(|arc| (*arc).clone()).collect();
+                (name_searchable && term.is_found_in(&doc_name_lower, doc_name_hiragana.as_deref().map(String::as_str)))
+                    // エイリアスのチェック
+                    || alias_forms.iter().any(|(lower, hiragana)| term.is_found_in(lower, hiragana.as_deref().map(String::as_str)))
+            });
+
+            if all_found && seen.insert(Arc::clone(doc_name)) {
+                // キーワードを含む最初のエイリアスを一致箇所とする
+                let matched = aliases.iter()
+                    .zip(&alias_forms)
+                    .find(|(_, (lower, hiragana))| {
+                        terms.iter().any(|term| term.is_found_in(lower, hiragana.as_deref().map(String::as_str)))
+                    })
+                    .map_or(doc_name, |(alias, _)| alias);
+                matches.push(Candidate {
+                    priority: MatchPriority::AliasPartial,
+                    doc_name: Arc::clone(doc_name),
+                    matched: Arc::clone(matched),
+                });
+                if matches.len() >= limit {
+                    return matches;
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// 1つのドキュメントに対する最良の一致優先度と、その一致箇所を求める
+    fn match_document(&mut self, doc_name: &Arc<String>, aliases: &[Arc<String>], terms: &[QueryTerm]) -> Option<(MatchPriority, Arc<String>)> {
+        let aliases = self.searched_aliases(aliases);
+        let name_searchable = self.name_searchable(doc_name);
+        let doc_lower = self.cache.get_lowercase(doc_name);
+        let doc_hiragana = self.hiragana_of(doc_name);
+        let mut best: Option<(MatchPriority, Arc<String>)> = None;
+
+        for term in terms {
+            if name_searchable {
+                // 1. 名前の完全一致（最高優先度なので即座に終了）
+                if term.is_exact(&doc_lower) {
+                    return Some((MatchPriority::NameExact, Arc::clone(doc_name)));
+                }
+
+                // 3. 名前の前方一致
+                if term.is_prefix_of(&doc_lower) {
+                    consider(&mut best, MatchPriority::NamePrefix, doc_name);
+                }
+                // 5. 名前の部分一致（ひらがな変換含む）
+                else if term.is_partial_of(&doc_lower, doc_hiragana.as_deref().map(String::as_str)) {
+                    consider(&mut best, MatchPriority::NamePartial, doc_name);
+                }
+            }
+
+            for alias in aliases {
+                let alias_lower = self.cache.get_lowercase(alias);
+
+                // 2. エイリアスの完全一致
+                if term.is_exact(&alias_lower) {
+                    consider(&mut best, MatchPriority::AliasExact, alias);
+                }
+                // 4. エイリアスの前方一致
+                else if term.is_prefix_of(&alias_lower) {
+                    consider(&mut best, MatchPriority::AliasPrefix, alias);
+                }
+                // 6. エイリアスの部分一致（ひらがな変換含む）
+                else if best.as_ref().is_none_or(|(p, _)| *p > MatchPriority::AliasPartial) {
+                    let alias_hiragana = self.hiragana_of(alias);
+                    if term.is_partial_of(&alias_lower, alias_hiragana.as_deref().map(String::as_str)) {
+                        consider(&mut best, MatchPriority::AliasPartial, alias);
                     }
                 }
             }
         }
 
-        matches.into_iter().map(|arc| (*arc).clone()).collect()
+        best
+    }
+
+    /// 一致したドキュメントを優先度とともに集める
+    ///
+    /// `early_exit` に `(n, priority)` を指定すると、`priority` 以上の優先度の候補が `n` 件集まった時点で打ち切る。
+    /// 調べるドキュメントは最大 `budget` 件
+    fn collect_candidates(&mut self, queries: &[String], early_exit: Option<(usize, MatchPriority)>, budget: usize) -> Vec<Candidate> {
+        let terms: Vec<QueryTerm> = queries.iter()
+            .map(|query| QueryTerm::new(query, self.settings))
+            .collect();
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let mut settled = 0;
+        let doc_aliases = self.doc_aliases;
+
+        for (doc_name, aliases) in doc_aliases.iter().take(budget) {
+            if let Some((priority, matched)) = self.match_document(doc_name, aliases, &terms) {
+                candidates.push(Candidate { priority, doc_name: Arc::clone(doc_name), matched });
+                if let Some((n, at_least)) = early_exit && priority <= at_least {
+                    settled += 1;
+                    if settled >= n {
+                        break; // 十分な候補が集まったら終了
+                    }
+                }
+            }
+        }
+
+        candidates
     }
 
     /// 優先度ベースの統合検索
-    pub fn search_unified(&mut self, queries: &[String], limit: usize) -> Vec<String> {
-        let mut candidates: Vec<(MatchPriority, Arc<String>)> = Vec::new();
-        let mut seen = HashSet::with_capacity_and_hasher(limit * 2, Default::default());
+    ///
+    /// ドキュメントは順不同で走査するため、後から見つかる候補に順位を抜かれない最高優先度（名前の完全一致）の
+    /// 候補が `limit` 件集まったときだけ走査を打ち切る
+    pub fn search_unified(&mut self, queries: &[String], limit: usize, budget: usize) -> Vec<Candidate> {
+        let mut candidates = self.collect_candidates(queries, Some((limit, MatchPriority::NameExact)), budget);
 
-        for (doc_name, aliases) in self.doc_aliases.iter() {
-            if seen.contains(doc_name) {
+        // 優先度でソートして結果を返す
+        candidates.sort_by_key(|c| c.priority);
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// 検索を実行し、優先度順の結果を返す
+    ///
+    /// スペース区切りの単一クエリはAND検索、それ以外は優先度ベースの統合検索
+    pub fn search_ranked(&mut self, queries: &[String], limit: usize) -> Vec<Candidate> {
+        self.search_ranked_with_boost_terms(queries, &[], 0.0, limit)
+    }
+
+    /// `boost_terms` のいずれかを名前・エイリアスに含む結果のブーストに `weight` を加えて検索する
+    ///
+    /// ブースト語は一致するドキュメントを増やさず、同じ優先度の中での順位だけを変える
+    pub fn search_ranked_with_boost_terms(&mut self, queries: &[String], boost_terms: &[String], weight: f64, limit: usize) -> Vec<Candidate> {
+        if self.doc_aliases.is_empty() {
+            return Vec::new();
+        }
+        let boosts_terms = weight != 0.0 && !boost_terms.is_empty();
+
+        // 同じ優先度の中での順位が変わる設定があれば、候補を打ち切らずに集める
+        let ordered = self.orders_within_tier() || boosts_terms;
+        let collect_limit = if ordered { usize::MAX } else { limit };
+        // 予算を超えたら、それまでに見つかった結果だけを返す
+        let budget = self.settings.search_budget.unwrap_or(usize::MAX);
+
+        let mut results = match and_keywords(queries, self.settings) {
+            Some(keywords) => self.search_and(keywords, collect_limit, budget),
+            None => self.search_unified(queries, collect_limit, budget),
+        };
+        if ordered {
+            let mut bonuses = HashMap::default();
+            self.add_word_start_bonuses(queries, &results, &mut bonuses);
+            if boosts_terms {
+                self.add_boost_term_bonuses(boost_terms, weight, &results, &mut bonuses);
+            }
+            results.sort_by(|a, b| self.compare_candidates(a, b, &bonuses));
+            results.truncate(limit);
+        }
+        if self.settings.suppress_partials_on_exact
+            && results.iter().any(|c| c.priority <= MatchPriority::AliasExact)
+        {
+            results.retain(|c| c.priority <= MatchPriority::AliasPrefix);
+        }
+        results
+    }
+
+    /// 検索語が単語の先頭で一致した候補に、単語の先頭での一致のボーナスを加える
+    fn add_word_start_bonuses(&mut self, queries: &[String], candidates: &[Candidate], bonuses: &mut HashMap<Arc<String>, f64>) {
+        if self.settings.word_start_bonus == 0.0 {
+            return;
+        }
+        let terms: Vec<&str> = queries.iter()
+            .flat_map(|query| query.split_whitespace())
+            .collect();
+        for c in candidates {
+            let matched = self.cache.get_lowercase(&c.matched);
+            if terms.iter().any(|term| starts_word(&matched, term)) {
+                *bonuses.entry(Arc::clone(&c.doc_name)).or_default() += self.settings.word_start_bonus;
+            }
+        }
+    }
+
+    /// ブースト語のいずれかを名前・エイリアスに含む候補に `weight` を加える
+    fn add_boost_term_bonuses(&mut self, boost_terms: &[String], weight: f64, candidates: &[Candidate], bonuses: &mut HashMap<Arc<String>, f64>) {
+        let terms: Vec<QueryTerm> = boost_terms.iter()
+            .filter(|term| !term.is_empty())
+            .map(|term| QueryTerm::new(term, self.settings))
+            .collect();
+        let doc_aliases = self.doc_aliases;
+        for c in candidates {
+            let name_searchable = self.attrs.is_name_searchable(&c.doc_name);
+            let texts = doc_aliases.get(&c.doc_name).into_iter()
+                .flatten()
+                .chain(name_searchable.then_some(&c.doc_name));
+            let found = texts.into_iter().any(|text| {
+                let lower = self.cache.get_lowercase(text);
+                let hiragana = self.hiragana_of(text);
+                terms.iter().any(|term| term.is_found_in(&lower, hiragana.as_deref().map(String::as_str)))
+            });
+            if found {
+                *bonuses.entry(Arc::clone(&c.doc_name)).or_default() += weight;
+            }
+        }
+    }
+
+    /// ブーストなど、同じ優先度の中での並び順を決める設定があるか
+    fn orders_within_tier(&self) -> bool {
+        !self.attrs.boosts.is_empty()
+            || self.settings.alias_position_decay
+            || self.settings.tie_break != TieBreak::None
+            || self.settings.word_start_bonus != 0.0
+            || self.settings.prefer_exact_case
+            || !self.attrs.alias_weights.is_empty()
+    }
+
+    /// 優先度、ブースト（大きい順）、一致したエイリアスの位置（前にあるほど上位）、
+    /// 同順位の並べ方の設定の順に比較する
+    ///
+    /// `bonuses` に含まれる候補は、その値（単語の先頭での一致やブースト語のボーナス）がブーストに加わる
+    fn compare_candidates(&self, a: &Candidate, b: &Candidate, bonuses: &HashMap<Arc<String>, f64>) -> Ordering {
+        let boost = |c: &Candidate| {
+            self.attrs.boost(&c.doc_name) + bonuses.get(&c.doc_name).copied().unwrap_or(0.0)
+        };
+        a.priority.cmp(&b.priority)
+            .then_with(|| self.matches_case(b).cmp(&self.matches_case(a)))
+            .then_with(|| boost(b).total_cmp(&boost(a)))
+            .then_with(|| self.alias_weight(b).total_cmp(&self.alias_weight(a)))
+            .then_with(|| {
+                if self.settings.alias_position_decay {
+                    self.alias_position(a).cmp(&self.alias_position(b))
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .then_with(|| match self.settings.tie_break {
+                TieBreak::None => Ordering::Equal,
+                TieBreak::Lexicographic => a.doc_name.cmp(&b.doc_name),
+                TieBreak::Insertion => self.attrs.seq(&a.doc_name).cmp(&self.attrs.seq(&b.doc_name)),
+                TieBreak::AliasCount => self.alias_count(b).cmp(&self.alias_count(a)),
+            })
+    }
+
+    /// 完全一致した名前・エイリアスが、大文字・小文字まで検索語と一致するか
+    fn matches_case(&self, candidate: &Candidate) -> bool {
+        if !self.settings.prefer_exact_case || candidate.priority > MatchPriority::AliasExact {
+            return false;
+        }
+        // 検索語は指定文字を取り除いてあるため、名前・エイリアスからも同じように取り除いて比べる
+        let matched = strip(&candidate.matched, &self.cache.strip_chars);
+        self.cased_queries.contains(&matched)
+    }
+
+    /// 候補のドキュメントのエイリアスの数
+    fn alias_count(&self, candidate: &Candidate) -> usize {
+        self.doc_aliases.get(&candidate.doc_name).map_or(0, Vec::len)
+    }
+
+    /// 一致したエイリアスの重み（名前での一致は 1）
+    fn alias_weight(&self, candidate: &Candidate) -> f64 {
+        if Arc::ptr_eq(&candidate.doc_name, &candidate.matched) {
+            return 1.0;
+        }
+        self.attrs.alias_weight(&candidate.doc_name, &candidate.matched)
+    }
+
+    /// 一致したエイリアスがドキュメントの何番目のエイリアスか（名前での一致は 0）
+    fn alias_position(&self, candidate: &Candidate) -> usize {
+        self.doc_aliases.get(&candidate.doc_name)
+            .and_then(|aliases| aliases.iter().position(|alias| Arc::ptr_eq(alias, &candidate.matched)))
+            .unwrap_or(0)
+    }
+
+    /// 最も順位の高い1件だけを返す（候補を並べ替えず、最良のものだけを保持する）
+    pub fn search_best(&mut self, queries: &[String]) -> Option<Candidate> {
+        if self.doc_aliases.is_empty() {
+            return None;
+        }
+        let budget = self.settings.search_budget.unwrap_or(usize::MAX);
+        let mut bonuses = HashMap::default();
+
+        if let Some(keywords) = and_keywords(queries, self.settings) {
+            let candidates = self.search_and(keywords, usize::MAX, budget);
+            self.add_word_start_bonuses(queries, &candidates, &mut bonuses);
+            return candidates.into_iter().min_by(|a, b| self.compare_candidates(a, b, &bonuses));
+        }
+
+        let terms: Vec<QueryTerm> = queries.iter()
+            .map(|query| QueryTerm::new(query, self.settings))
+            .collect();
+        let ordered = self.orders_within_tier();
+        let mut best: Option<Candidate> = None;
+        let doc_aliases = self.doc_aliases;
+
+        for (doc_name, aliases) in doc_aliases.iter().take(budget) {
+            let Some((priority, matched)) = self.match_document(doc_name, aliases, &terms) else {
                 continue;
+            };
+            let candidate = Candidate { priority, doc_name: Arc::clone(doc_name), matched };
+            self.add_word_start_bonuses(queries, std::slice::from_ref(&candidate), &mut bonuses);
+            if best.as_ref().is_none_or(|b| self.compare_candidates(&candidate, b, &bonuses) == Ordering::Less) {
+                best = Some(candidate);
             }
-            
-            let mut best_priority = None;
-            
-            for query in queries {
-                let doc_lower = self.cache.get_lowercase(doc_name);
-                let doc_hiragana = self.cache.get_hiragana(doc_name);
-                
-                // 1. 名前の完全一致
-                if doc_lower.as_str() == query {
-                    best_priority = Some(MatchPriority::NameExact);
-                    break; // 最高優先度なので即座に終了
-                }
-                
-                // Romajiからひらがなに変換した場合の完全一致もチェック
-                let query_hiragana = query.to_hiragana();
-                if doc_lower.as_str() == &query_hiragana {
-                    best_priority = Some(MatchPriority::NameExact);
-                    break;
-                }
-                
-                // 3. 名前の前方一致
-                if best_priority.map_or(true, |p| p > MatchPriority::NamePrefix) {
-                    if doc_lower.starts_with(query) || doc_lower.starts_with(&query_hiragana) {
-                        best_priority = Some(MatchPriority::NamePrefix);
+            // 同じ優先度の中での並び順の設定がなければ、名前の完全一致より上位のものはない
+            if !ordered && best.as_ref().is_some_and(|b| b.priority == MatchPriority::NameExact) {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// 一致するドキュメントが1件でもあるか（最初の一致で打ち切る）
+    pub fn has_any_match(&mut self, queries: &[String]) -> bool {
+        if let Some(keywords) = and_keywords(queries, self.settings) {
+            return !self.search_and(keywords, 1, usize::MAX).is_empty();
+        }
+        !self.collect_candidates(queries, Some((1, MatchPriority::AliasFuzzyPrefix)), usize::MAX).is_empty()
+    }
+
+    /// 一致するすべての候補（順位付けや打ち切りは行わない）
+    pub fn matched_candidates(&mut self, queries: &[String]) -> Vec<Candidate> {
+        match and_keywords(queries, self.settings) {
+            Some(keywords) => self.search_and(keywords, usize::MAX, usize::MAX),
+            None => self.collect_candidates(queries, None, usize::MAX),
+        }
+    }
+
+    /// 自分の名前で検索しても、前方一致以上の一致にならないドキュメント（インポート時の点検用）
+    ///
+    /// 名前は検索時と同じく正規化してクエリにする。空白を含む名前はAND検索になり部分一致にしかならない
+    pub fn only_partially_self_searchable(&mut self) -> Vec<Arc<String>> {
+        let doc_aliases = self.doc_aliases;
+        let mut flagged = Vec::new();
+
+        for (doc_name, aliases) in doc_aliases.iter() {
+            let query = self.cache.get_lowercase(doc_name);
+            let queries = std::slice::from_ref(&*query);
+            let self_searchable = !query.is_empty()
+                && and_keywords(queries, self.settings).is_none()
+                && {
+                    let terms = [QueryTerm::new(&query, self.settings)];
+                    self.match_document(doc_name, aliases, &terms)
+                        .is_some_and(|(priority, _)| priority <= MatchPriority::AliasPrefix)
+                };
+            if !self_searchable {
+                flagged.push(Arc::clone(doc_name));
+            }
+        }
+
+        flagged
+    }
+
+    /// 一致するすべてのドキュメント（順位付けや打ち切りは行わない）
+    pub fn matched_documents(&mut self, queries: &[String]) -> Vec<Arc<String>> {
+        self.matched_candidates(queries).into_iter().map(|c| c.doc_name).collect()
+    }
+
+    /// 一致するドキュメント数を数える（順位付けは行わない）
+    pub fn count_matches(&mut self, queries: &[String]) -> usize {
+        self.matched_documents(queries).len()
+    }
+
+    /// タイプミスを許容する前方一致検索（オートコンプリート用）
+    pub fn search_fuzzy_prefix(&mut self, queries: &[String], max_distance: usize, limit: usize) -> Vec<Candidate> {
+        let max_distance = max_distance.min(MAX_FUZZY_DISTANCE);
+        let terms: Vec<QueryTerm> = queries.iter()
+            .map(|query| QueryTerm::new(query, self.settings))
+            .collect();
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let doc_aliases = self.doc_aliases;
+
+        for (doc_name, aliases) in doc_aliases.iter() {
+            let name_searchable = self.attrs.is_name_searchable(doc_name);
+            let doc_lower = self.cache.get_lowercase(doc_name);
+            let mut best: Option<(MatchPriority, Arc<String>)> = None;
+
+            for term in &terms {
+                if name_searchable {
+                    if term.is_exact(&doc_lower) {
+                        consider(&mut best, MatchPriority::NameExact, doc_name);
+                    } else if term.is_prefix_of(&doc_lower) {
+                        consider(&mut best, MatchPriority::NamePrefix, doc_name);
+                    } else if term.is_fuzzy_prefix_of(&doc_lower, max_distance) {
+                        consider(&mut best, MatchPriority::NameFuzzyPrefix, doc_name);
                     }
                 }
-                
-                // 5. 名前の部分一致（ひらがな変換含む）
-                if best_priority.map_or(true, |p| p > MatchPriority::NamePartial) {
-                    if doc_lower.contains(query) || doc_lower.contains(&query_hiragana) {
-                        best_priority = Some(MatchPriority::NamePartial);
-                    } else if let Some(hiragana) = &doc_hiragana {
-                        if hiragana.contains(&query.to_hiragana()) {
-                            best_priority = Some(MatchPriority::NamePartial);
-                        }
+
+                for alias in aliases {
+                    let alias_lower = self.cache.get_lowercase(alias);
+                    if term.is_exact(&alias_lower) {
+                        consider(&mut best, MatchPriority::AliasExact, alias);
+                    } else if term.is_prefix_of(&alias_lower) {
+                        consider(&mut best, MatchPriority::AliasPrefix, alias);
+                    } else if term.is_fuzzy_prefix_of(&alias_lower, max_distance) {
+                        consider(&mut best, MatchPriority::AliasFuzzyPrefix, alias);
                     }
                 }
-                
-                // エイリアスのチェック（名前の完全一致でない場合のみ）
-                if best_priority != Some(MatchPriority::NameExact) {
-                    for alias in aliases {
-                        let alias_lower = self.cache.get_lowercase(alias);
-                        let alias_hiragana = self.cache.get_hiragana(alias);
-                        
-                        // 2. エイリアスの完全一致
-                        if alias_lower.as_str() == query && 
-                           best_priority.map_or(true, |p| p > MatchPriority::AliasExact) {
-                            best_priority = Some(MatchPriority::AliasExact);
-                        }
-                        // Romajiからひらがなに変換した場合のエイリアス完全一致もチェック
-                        else if alias_lower.as_str() == &query_hiragana && 
-                                best_priority.map_or(true, |p| p > MatchPriority::AliasExact) {
-                            best_priority = Some(MatchPriority::AliasExact);
-                        }
-                        // 4. エイリアスの前方一致
-                        else if (alias_lower.starts_with(query) || alias_lower.starts_with(&query_hiragana)) && 
-                                best_priority.map_or(true, |p| p > MatchPriority::AliasPrefix) {
-                            best_priority = Some(MatchPriority::AliasPrefix);
-                        }
-                        // 6. エイリアスの部分一致（ひらがな変換含む）
-                        else if best_priority.map_or(true, |p| p >= MatchPriority::AliasPartial) {
-                            if alias_lower.contains(query) {
-                                best_priority = Some(MatchPriority::AliasPartial);
-                            } else if alias_lower.contains(&query_hiragana) {
-                                // ローマ字クエリをひらがなに変換して直接比較
-                                best_priority = Some(MatchPriority::AliasPartial);
-                            } else if let Some(hiragana) = &alias_hiragana {
-                                if hiragana.contains(&query.to_hiragana()) {
-                                    best_priority = Some(MatchPriority::AliasPartial);
-                                }
-                            }
-                        }
+            }
+
+            if let Some((priority, matched)) = best {
+                candidates.push(Candidate { priority, doc_name: Arc::clone(doc_name), matched });
+            }
+        }
+
+        // 完全な前方一致をあいまい一致より上位に並べる
+        candidates.sort_by(|a, b| self.compare_candidates(a, b, &HashMap::default()));
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// すべての検索語が、名前またはエイリアスに含まれるか、あいまい前方一致するドキュメントを返す
+    ///
+    /// 検索語は空白で区切る。タイプミスのない一致をあいまい一致より上位に、
+    /// 名前だけですべての検索語に一致するものをエイリアスも必要なものより上位に並べる
+    pub fn search_fuzzy_and(&mut self, queries: &[String], max_distance: usize, limit: usize) -> Vec<Candidate> {
+        let max_distance = max_distance.min(MAX_FUZZY_DISTANCE);
+        let terms: Vec<QueryTerm> = queries.iter()
+            .flat_map(|query| query.split(' '))
+            .filter(|keyword| !keyword.is_empty())
+            .map(|keyword| QueryTerm::new(keyword, self.settings))
+            .collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let doc_aliases = self.doc_aliases;
+
+        for (doc_name, aliases) in doc_aliases.iter() {
+            let aliases = self.searched_aliases(aliases);
+            let name_searchable = self.name_searchable(doc_name);
+            let doc_lower = self.cache.get_lowercase(doc_name);
+            let doc_hiragana = self.hiragana_of(doc_name);
+            let alias_forms: Vec<(Arc<String>, Option<Arc<String>>)> = aliases.iter()
+                .map(|alias| (self.cache.get_lowercase(alias), self.hiragana_of(alias)))
+                .collect();
+
+            let mut name_only = true;
+            let mut fuzzy = false;
+            let mut matched_alias: Option<&Arc<String>> = None;
+            let all_found = terms.iter().all(|term| {
+                let found_in = |lower: &str, hiragana: Option<&Arc<String>>, fuzzy: bool| {
+                    if fuzzy {
+                        term.is_fuzzy_prefix_of(lower, max_distance)
+                    } else {
+                        term.is_found_in(lower, hiragana.map(|h| h.as_str()))
+                    }
+                };
+                // タイプミスのない一致を先に探す
+                for fuzzy_match in [false, true] {
+                    if name_searchable && found_in(&doc_lower, doc_hiragana.as_ref(), fuzzy_match) {
+                        fuzzy |= fuzzy_match;
+                        return true;
+                    }
+                    let alias = aliases.iter()
+                        .zip(&alias_forms)
+                        .find(|(_, (lower, hiragana))| found_in(lower, hiragana.as_ref(), fuzzy_match));
+                    if let Some((alias, _)) = alias {
+                        fuzzy |= fuzzy_match;
+                        name_only = false;
+                        matched_alias.get_or_insert(alias);
+                        return true;
                     }
                 }
+                false
+            });
+
+            if all_found {
+                let priority = match (name_only, fuzzy) {
+                    (true, false) => MatchPriority::NamePartial,
+                    (false, false) => MatchPriority::AliasPartial,
+                    (true, true) => MatchPriority::NameFuzzyPrefix,
+                    (false, true) => MatchPriority::AliasFuzzyPrefix,
+                };
+                let matched = matched_alias.unwrap_or(doc_name);
+                candidates.push(Candidate { priority, doc_name: Arc::clone(doc_name), matched: Arc::clone(matched) });
+            }
+        }
+
+        candidates.sort_by(|a, b| self.compare_candidates(a, b, &HashMap::default()));
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// 各検索語の近くにある（編集距離が小さい）名前・エイリアスを、使われているドキュメントの多い順に提案する
+    ///
+    /// `MIN_FUZZY_QUERY_CHARS` 文字未満の検索語と、そのまま存在する検索語は対象にしない
+    pub fn suggest(&mut self, queries: &[String], max_suggestions: usize) -> Vec<String> {
+        // 名前・エイリアスごとに、それを持つドキュメントの数を数える
+        let mut vocabulary: HashMap<String, usize> = HashMap::default();
+        let doc_aliases = self.doc_aliases;
+        for (doc_name, aliases) in doc_aliases.iter() {
+            let mut terms: HashSet<Arc<String>> = HashSet::default();
+            if self.name_searchable(doc_name) {
+                terms.insert(self.cache.get_lowercase(doc_name));
+            }
+            for alias in aliases {
+                terms.insert(self.cache.get_lowercase(alias));
             }
-            
-            if let Some(priority) = best_priority {
-                seen.insert(Arc::clone(doc_name));
-                candidates.push((priority, Arc::clone(doc_name)));
-                if candidates.len() >= limit * 2 {
-                    break; // 十分な候補が集まったら終了
+            for term in terms {
+                *vocabulary.entry((*term).clone()).or_default() += 1;
+            }
+        }
+
+        let mut suggestions: Vec<(usize, usize, &String)> = Vec::new();
+        for query in queries.iter().flat_map(|query| query.split_whitespace()) {
+            let chars: Vec<char> = query.chars().collect();
+            if chars.len() < MIN_FUZZY_QUERY_CHARS || vocabulary.contains_key(query) {
+                continue;
+            }
+            for (term, &df) in &vocabulary {
+                if let Some(distance) = edit_distance(&chars, term, MAX_SUGGEST_DISTANCE) {
+                    suggestions.push((df, distance, term));
                 }
             }
         }
 
-        // 優先度でソートして結果を返す
-        candidates.sort_by_key(|(p, _)| *p);
-        candidates.into_iter()
-            .take(limit)
-            .map(|(_, name)| (*name).clone())
+        // よく使われている語を先に、同じなら編集距離の小さい順、名前順に並べる
+        suggestions.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then_with(|| a.2.cmp(b.2)));
+        let mut seen = HashSet::default();
+        suggestions.into_iter()
+            .filter(|(_, _, term)| seen.insert(*term))
+            .take(max_suggestions)
+            .map(|(_, _, term)| term.clone())
             .collect()
     }
-}
\ No newline at end of file
+
+    /// 名前またはエイリアスが `suffix`（正規化済み）で終わるドキュメントを返す
+    ///
+    /// 完全一致は完全一致として、それ以外は部分一致として優先度順に並べる。
+    /// 索引を使わず、すべての名前・エイリアスを走査する
+    pub fn search_suffix(&mut self, suffix: &str, limit: usize) -> Vec<Candidate> {
+        if suffix.is_empty() {
+            return Vec::new();
+        }
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let doc_aliases = self.doc_aliases;
+
+        for (doc_name, aliases) in doc_aliases.iter() {
+            let mut best: Option<(MatchPriority, Arc<String>)> = None;
+            if self.name_searchable(doc_name) {
+                let doc_lower = self.cache.get_lowercase(doc_name);
+                if doc_lower.as_str() == suffix {
+                    consider(&mut best, MatchPriority::NameExact, doc_name);
+                } else if doc_lower.ends_with(suffix) {
+                    consider(&mut best, MatchPriority::NamePartial, doc_name);
+                }
+            }
+            for alias in aliases {
+                let alias_lower = self.cache.get_lowercase(alias);
+                if alias_lower.as_str() == suffix {
+                    consider(&mut best, MatchPriority::AliasExact, alias);
+                } else if alias_lower.ends_with(suffix) {
+                    consider(&mut best, MatchPriority::AliasPartial, alias);
+                }
+            }
+
+            if let Some((priority, matched)) = best {
+                candidates.push(Candidate { priority, doc_name: Arc::clone(doc_name), matched });
+            }
+        }
+
+        candidates.sort_by(|a, b| self.compare_candidates(a, b, &HashMap::default()));
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// `text`（名前またはエイリアス）の中で検索語に一致する範囲を UTF-16 オフセットで返す
+    ///
+    /// 検索語は空白で区切り、ローマ字のひらがな変換も照合する
+    pub fn highlight(&self, text: &str, queries: &[String]) -> Vec<[usize; 2]> {
+        let terms: Vec<QueryTerm> = queries.iter()
+            .flat_map(|query| query.split(' '))
+            .filter(|keyword| !keyword.is_empty())
+            .map(|keyword| QueryTerm::new(keyword, self.settings))
+            .collect();
+        let needles: Vec<&str> = terms.iter()
+            .flat_map(|term| std::iter::once(term.text).chain(term.hiragana.as_deref()))
+            .collect();
+        let removed = self.settings.removed_chars();
+        find_ranges(text, &needles, |c| {
+            if removed.contains(c) {
+                return String::new();
+            }
+            let lower = lowercase(c.encode_utf8(&mut [0; 4]), self.settings.locale);
+            if self.settings.kana_normalization { normalize_kana(&lower) } else { lower }
+        })
+    }
+
+    /// 前方一致検索用の索引がなければ作る
+    pub fn build_prefix_index(&mut self) {
+        if self.cache.prefix_index.is_none() {
+            let index = PrefixIndex::build(self.doc_aliases, self.cache);
+            self.cache.prefix_index = Some(index);
+        }
+    }
+
+    /// 索引を使い、名前またはエイリアスが検索語で始まるドキュメントだけを返す（オートコンプリート用）
+    ///
+    /// 空白を含まない検索語では、結果は通常の検索の完全一致・前方一致と同じで、部分一致は含まない。
+    /// 空白を含む検索語は `and_keywords` で分割せず、そのまま前方一致として照合する。
+    /// 同じ順位の候補は同順位の並べ方の設定に従い、残りは名前順に並べる
+    pub fn search_prefix_indexed(&mut self, queries: &[String], limit: usize) -> Vec<Candidate> {
+        self.build_prefix_index();
+        let terms: Vec<QueryTerm> = queries.iter()
+            .map(|query| QueryTerm::new(query, self.settings))
+            .collect();
+        let Some(index) = self.cache.prefix_index.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut best: HashMap<Arc<String>, (MatchPriority, Arc<String>)> = HashMap::default();
+        for term in &terms {
+            for form in std::iter::once(term.text).chain(term.hiragana.as_deref()) {
+                for entry in index.with_prefix(form) {
+                    let exact = entry.key.as_str() == form;
+                    if term.exact_only && !exact {
+                        continue;
+                    }
+                    let priority = match (entry.is_name, exact) {
+                        (true, true) => MatchPriority::NameExact,
+                        (true, false) => MatchPriority::NamePrefix,
+                        (false, true) => MatchPriority::AliasExact,
+                        (false, false) => MatchPriority::AliasPrefix,
+                    };
+                    let searchable = if entry.is_name {
+                        self.fields.name && self.attrs.is_name_searchable(&entry.doc_name)
+                    } else {
+                        self.fields.alias
+                    };
+                    if !searchable {
+                        continue;
+                    }
+                    let slot = best.entry(Arc::clone(&entry.doc_name)).or_insert_with(|| (priority, Arc::clone(&entry.matched)));
+                    if priority < slot.0 {
+                        *slot = (priority, Arc::clone(&entry.matched));
+                    }
+                }
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = best.into_iter()
+            .map(|(doc_name, (priority, matched))| Candidate { priority, doc_name, matched })
+            .collect();
+        candidates.sort_by(|a, b| {
+            self.compare_candidates(a, b, &HashMap::default()).then_with(|| a.doc_name.cmp(&b.doc_name))
+        });
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// 名前・エイリアスとの2-gramの重なり（Jaccard係数）が `min_overlap` 以上のドキュメントを返す
+    ///
+    /// 重なりの大きい順（同じ値なら名前順）に並べる。各要素はドキュメント名・最も重なった名前またはエイリアス・重なり
+    pub fn search_gram_overlap(&mut self, queries: &[String], min_overlap: f64, limit: usize) -> Vec<(Arc<String>, Arc<String>, f64)> {
+        let doc_aliases = self.doc_aliases;
+        let mut candidates: Vec<(Arc<String>, Arc<String>, f64)> = Vec::new();
+
+        for (doc_name, aliases) in doc_aliases.iter() {
+            let name_searchable = self.attrs.is_name_searchable(doc_name);
+            let mut best: Option<(f64, &Arc<String>)> = None;
+            let mut consider_overlap = |overlap: f64, matched| {
+                if best.is_none_or(|(b, _)| overlap > b) {
+                    best = Some((overlap, matched));
+                }
+            };
+
+            for query in queries {
+                if name_searchable {
+                    consider_overlap(bigram_overlap(query, &self.cache.get_lowercase(doc_name)), doc_name);
+                }
+                for alias in aliases {
+                    consider_overlap(bigram_overlap(query, &self.cache.get_lowercase(alias)), alias);
+                }
+            }
+
+            if let Some((overlap, matched)) = best
+                && overlap > 0.0
+                && overlap >= min_overlap
+            {
+                candidates.push((Arc::clone(doc_name), Arc::clone(matched), overlap));
+            }
+        }
+
+        candidates.sort_by(|(a_name, _, a), (b_name, _, b)| b.total_cmp(a).then_with(|| a_name.cmp(b_name)));
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// 名前またはエイリアスの完全一致のみを返す（逆引きインデックスを直接参照する）
+    pub fn search_exact(&mut self, term: &str, limit: usize) -> Vec<Candidate> {
+        let locale = self.settings.locale;
+        let mut keys = vec![self.cache.key(term)];
+        // ローマ字は通常の検索と同じく、ロケールに従って小文字化してから変換する
+        let lower = lowercase(&strip(term, &self.cache.strip_chars), locale);
+        if self.settings.kana_conversion
+            && let Some(hiragana) = romaji_to_hiragana(&lower, &self.settings.kana_overrides)
+        {
+            let hiragana = self.cache.key(&hiragana);
+            if hiragana != keys[0] {
+                keys.push(hiragana);
+            }
+        }
+
+        let mut matches: Vec<Candidate> = Vec::new();
+        let mut seen = HashSet::default();
+
+        // 名前の完全一致を先に、エイリアスの完全一致を後に並べる
+        let name_hits = keys.iter()
+            .filter_map(|key| self.cache.name_to_doc.get(key))
+            .flatten()
+            .filter(|doc_name| self.attrs.is_name_searchable(doc_name));
+        for doc_name in name_hits {
+            if self.doc_aliases.contains_key(doc_name) && seen.insert(Arc::clone(doc_name)) {
+                matches.push(Candidate {
+                    priority: MatchPriority::NameExact,
+                    doc_name: Arc::clone(doc_name),
+                    matched: Arc::clone(doc_name),
+                });
+            }
+        }
+
+        let alias_hits = keys.iter()
+            .filter_map(|key| self.cache.alias_to_doc.get(key))
+            .flatten();
+        for doc_name in alias_hits {
+            let Some(aliases) = self.doc_aliases.get(doc_name) else { continue };
+            if !seen.insert(Arc::clone(doc_name)) {
+                continue;
+            }
+            // 逆引きインデックスはドキュメント名しか持たないため、一致したエイリアスを探し直す
+            let matched = aliases.iter()
+                .find(|alias| keys.contains(&self.cache.key(alias)))
+                .unwrap_or(doc_name);
+            matches.push(Candidate {
+                priority: MatchPriority::AliasExact,
+                doc_name: Arc::clone(doc_name),
+                matched: Arc::clone(matched),
+            });
+        }
+
+        matches.truncate(limit);
+        matches
+    }
+}
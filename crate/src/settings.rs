@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+
+/// クエリ内の空白の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WhitespaceMode {
+    /// 空白で区切ってAND検索する
+    Split,
+    /// 名前・エイリアス・クエリから空白を取り除いて照合する
+    Strip,
+    /// 空白も文字としてそのまま照合する
+    Keep,
+}
+
+impl WhitespaceMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "split" => Some(WhitespaceMode::Split),
+            "strip" => Some(WhitespaceMode::Strip),
+            "keep" => Some(WhitespaceMode::Keep),
+            _ => None,
+        }
+    }
+}
+
+/// 同じ優先度・ブーストの結果の並べ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TieBreak {
+    /// 並べ替えない（順序は不定）
+    None,
+    /// 名前の辞書順
+    Lexicographic,
+    /// 追加された順
+    Insertion,
+    /// エイリアスの多い順
+    #[serde(rename = "aliasCount")]
+    AliasCount,
+}
+
+impl TieBreak {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "none" => Some(TieBreak::None),
+            "lexicographic" => Some(TieBreak::Lexicographic),
+            "insertion" => Some(TieBreak::Insertion),
+            "aliasCount" => Some(TieBreak::AliasCount),
+            _ => None,
+        }
+    }
+}
+
+/// 大文字・小文字の変換に使うロケール
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    /// Unicode の標準的な変換
+    #[serde(rename = "default")]
+    Default,
+    /// トルコ語: `I` → `ı`、`İ` → `i`
+    #[serde(rename = "tr")]
+    Turkish,
+    /// ドイツ語: `ß` → `ss`
+    #[serde(rename = "de")]
+    German,
+}
+
+impl Locale {
+    pub fn parse(locale: &str) -> Option<Self> {
+        match locale {
+            "default" => Some(Locale::Default),
+            "tr" => Some(Locale::Turkish),
+            "de" => Some(Locale::German),
+            _ => None,
+        }
+    }
+}
+
+/// 検索の挙動を調整する設定
+///
+/// ダンプにはJSONとして保存されるため、項目を追加しても既存のダンプを読み込める。
+/// キーは `exportConfig` で JS に渡すため camelCase にし、以前のダンプの snake_case のキーも別名として受け付ける
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IndexSettings {
+    /// ローマ字のクエリをひらがなに変換して照合するか
+    #[serde(alias = "kana_conversion")]
+    pub kana_conversion: bool,
+    /// 数字のみのクエリを完全一致だけで照合するか
+    #[serde(alias = "numeric_exact_only")]
+    pub numeric_exact_only: bool,
+    /// 検索結果数の上限（`None` なら上限なし）
+    #[serde(alias = "max_limit")]
+    pub max_limit: Option<usize>,
+    /// 1回の検索で調べるドキュメント数の上限（`None` なら上限なし）
+    #[serde(alias = "search_budget")]
+    pub search_budget: Option<usize>,
+    /// 照合前に名前・エイリアス・クエリから取り除く文字
+    #[serde(alias = "strip_chars")]
+    pub strip_chars: String,
+    /// クエリ内の空白の扱い
+    #[serde(alias = "whitespace_mode")]
+    pub whitespace_mode: WhitespaceMode,
+    /// 名前・エイリアス・クエリの小文字化に使うロケール
+    pub locale: Locale,
+    /// 同じ優先度の中で、前にあるエイリアスでの一致を上位にするか
+    #[serde(alias = "alias_position_decay")]
+    pub alias_position_decay: bool,
+    /// 最終的に同順位となった結果の並べ方
+    #[serde(alias = "tie_break")]
+    pub tie_break: TieBreak,
+    /// 単語の先頭（名前・エイリアスの先頭や区切り文字の直後）での一致に加えるブースト
+    #[serde(alias = "word_start_bonus")]
+    pub word_start_bonus: f64,
+    /// 既存のドキュメントと同じ名前の追加を、置き換えずにエラーにするか
+    #[serde(alias = "append_only")]
+    pub append_only: bool,
+    /// 長音・小書きのかなの表記ゆれをまとめて照合するか
+    #[serde(alias = "kana_normalization")]
+    pub kana_normalization: bool,
+    /// wana_kana より優先するローマ字からひらがなへの変換（"uu" → "う" など）
+    #[serde(alias = "kana_overrides")]
+    pub kana_overrides: BTreeMap<String, String>,
+    /// 完全一致する結果があるとき、部分一致の結果を除くか
+    #[serde(alias = "suppress_partials_on_exact")]
+    pub suppress_partials_on_exact: bool,
+    /// 完全一致の中で、大文字・小文字まで検索語と一致するものを上位にするか
+    #[serde(alias = "prefer_exact_case")]
+    pub prefer_exact_case: bool,
+}
+
+impl Default for IndexSettings {
+    fn default() -> Self {
+        IndexSettings {
+            kana_conversion: true,
+            numeric_exact_only: false,
+            max_limit: None,
+            search_budget: None,
+            strip_chars: String::new(),
+            whitespace_mode: WhitespaceMode::Split,
+            locale: Locale::Default,
+            alias_position_decay: false,
+            tie_break: TieBreak::None,
+            word_start_bonus: 0.0,
+            append_only: false,
+            kana_normalization: false,
+            kana_overrides: BTreeMap::new(),
+            suppress_partials_on_exact: false,
+            prefer_exact_case: false,
+        }
+    }
+}
+
+impl IndexSettings {
+    /// 外部から読み込んだ設定が、各設定メソッドで受け付ける値の範囲に収まっているか
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.word_start_bonus.is_finite() {
+            return Err(format!("Word start bonus must be a finite number: {}", self.word_start_bonus));
+        }
+        for (romaji, hiragana) in &self.kana_overrides {
+            if romaji.is_empty() || !romaji.chars().all(|c| c.is_ascii_lowercase()) {
+                return Err(format!("Romaji must be lowercase ASCII letters: {}", romaji));
+            }
+            if hiragana.is_empty() {
+                return Err(format!("Hiragana must not be empty: {}", romaji));
+            }
+        }
+        Ok(())
+    }
+
+    /// 照合前に取り除く文字（空白を取り除くモードでは空白も含む）
+    pub fn removed_chars(&self) -> String {
+        match self.whitespace_mode {
+            WhitespaceMode::Strip => format!("{} \t\n\u{3000}", self.strip_chars),
+            WhitespaceMode::Split | WhitespaceMode::Keep => self.strip_chars.clone(),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 import { defu } from 'defu';
 import { Index } from '@/wasm/hanami_wasm_search.js';
-import type { InitInput } from '@/wasm/hanami_wasm_search.js';
+import type { InitInput, SearchResult } from '@/wasm/hanami_wasm_search.js';
+
+export type { SearchResult };
 
 type DeepPartial<T> = {
     [K in keyof T]?: T[K] extends Record<PropertyKey, unknown> ? DeepPartial<T[K]> : T[K] extends null ? undefined : T[K] | undefined;
@@ -17,42 +19,346 @@ export type SearchIndex = {
     emojis: {
         name: string;
         aliases: string[];
+        nameSearchable?: boolean;
+        category?: string;
+        meta?: unknown;
     }[];
 };
 
+export type MatchPriorityLabel =
+    | 'name_exact'
+    | 'alias_exact'
+    | 'name_prefix'
+    | 'alias_prefix'
+    | 'name_partial'
+    | 'alias_partial'
+    | 'name_fuzzy_prefix'
+    | 'alias_fuzzy_prefix';
+
+export type RomajiSearchResult = {
+    id: string;
+    romaji: string;
+};
+
+export type CategorizedSearchResult = {
+    id: string;
+    category: string | null;
+};
+
+export type HighlightedSearchResult = {
+    id: string;
+    nameRanges: [number, number][];
+    matchedAlias: string | null;
+    aliasRanges: [number, number][];
+};
+
+export type LabeledSearchResult = {
+    id: string;
+    priority: MatchPriorityLabel;
+};
+
+export type GramOverlapResult = {
+    id: string;
+    overlap: number;
+};
+
+export type WeightedAlias = {
+    alias: string;
+    weight: number;
+};
+
+export type DocumentUpdate = {
+    name: string;
+    aliases: string[];
+};
+
+export type TransactionOp =
+    | { op: 'add'; name: string; aliases: string[]; nameSearchable?: boolean; category?: string; meta?: unknown }
+    | { op: 'remove'; name: string }
+    | { op: 'update'; name: string; aliases: string[] };
+
+export type BatchUpdateResult = {
+    updated: number;
+    missing: string[];
+};
+
+export type MatchStat = {
+    token: string;
+    hits: number;
+};
+
+export type WhitespaceMode = 'split' | 'strip' | 'keep';
+
+export type SearchLocale = 'default' | 'tr' | 'de';
+
+export type TieBreak = 'none' | 'lexicographic' | 'insertion' | 'aliasCount';
+
+export type IndexConfig = {
+    kanaConversion: boolean;
+    numericExactOnly: boolean;
+    maxLimit: number | null;
+    searchBudget: number | null;
+    stripChars: string;
+    whitespaceMode: WhitespaceMode;
+    locale: SearchLocale;
+    aliasPositionDecay: boolean;
+    tieBreak: TieBreak;
+    wordStartBonus: number;
+    appendOnly: boolean;
+    kanaNormalization: boolean;
+    kanaOverrides: Record<string, string>;
+    suppressPartialsOnExact: boolean;
+    preferExactCase: boolean;
+};
+
+export type MatchDistribution = {
+    nameExact: number;
+    aliasExact: number;
+    namePrefix: number;
+    aliasPrefix: number;
+    namePartial: number;
+    aliasPartial: number;
+};
+
+export type SearchField = 'name' | 'alias';
+
+export type IntegrityReport = {
+    ok: boolean;
+    violations: string[];
+};
+
+export type SortMode = 'relevance' | 'alpha';
+
+export type SearchComparator = (aId: string, aPriority: number, bId: string, bPriority: number) => number;
+
 export type SearchEngineInstance = {
     addDocuments: (idx: SearchIndex) => void;
+    addDocumentsNdjson: (ndjson: string) => void;
     search: (query: string, limit?: number) => Promise<string[]>;
     searchNoLimit: (query: string) => Promise<string[]>;
     searchWithLimit: (query: string, limit: number) => Promise<string[]>;
+    searchWithLabels: (query: string, limit?: number) => Promise<LabeledSearchResult[]>;
+    searchWithRomaji: (query: string, limit?: number) => Promise<RomajiSearchResult[]>;
+    searchWithCategory: (query: string, limit?: number) => Promise<CategorizedSearchResult[]>;
+    searchWithHighlights: (query: string, limit?: number) => Promise<HighlightedSearchResult[]>;
+    searchWithBoostTerms: (query: string, boostTerms: string[], weight: number, limit?: number) => Promise<string[]>;
+    searchFields: (query: string, fields?: SearchField[], limit?: number) => Promise<string[]>;
+    searchTyped: (query: string, limit?: number) => Promise<SearchResult[]>;
+    searchFuzzyPrefix: (query: string, maxDistance: number, limit?: number) => Promise<string[]>;
+    searchFuzzyAnd: (query: string, maxDistance: number, limit?: number) => Promise<string[]>;
+    searchSorted: (query: string, sortMode: SortMode, limit?: number) => Promise<string[]>;
+    searchInCategories: (query: string, categories: string[], limit?: number) => Promise<string[]>;
+    searchDiversified: (query: string, maxPerCategory: number, limit?: number) => Promise<string[]>;
+    searchMinPriority: (query: string, minPriority: MatchPriorityLabel, limit?: number) => Promise<string[]>;
+    searchBinary: (query: string, limit?: number) => Promise<Uint8Array>;
+    searchByGramOverlap: (query: string, minOverlap: number, limit?: number) => Promise<GramOverlapResult[]>;
+    searchWithComparator: (query: string, compare: SearchComparator, limit?: number) => Promise<string[]>;
+    countMatches: (query: string) => number;
+    resultOverlap: (queryA: string, queryB: string, limit?: number) => number;
+    hasAnyMatch: (query: string) => boolean;
+    matchedDocumentSet: (query: string) => string[];
+    matchDistribution: (query: string) => MatchDistribution;
+    searchBest: (query: string) => Promise<string | null>;
+    searchExact: (term: string, limit?: number) => Promise<string[]>;
+    searchByChar: (ch: string) => Promise<string[]>;
+    /** 後方一致用の索引はなく、呼び出すたびにすべての名前・エイリアスを走査する（O(n)） */
+    searchSuffix: (suffix: string, limit?: number) => Promise<string[]>;
+    searchPrefixFast: (query: string, limit?: number) => Promise<string[]>;
+    buildPrefixIndex: () => void;
+    suggest: (query: string, maxSuggestions?: number) => string[];
     dump: () => Uint8Array;
     load: (data: Uint8Array) => void;
     removeDocument: (name: string) => boolean;
-    addDocument: (name: string, aliases: string[]) => void;
+    retainTop: (k: number) => number;
+    addDocument: (name: string, aliases: string[], nameSearchable?: boolean, category?: string) => void;
+    addDocumentWeighted: (name: string, weightedAliases: WeightedAlias[]) => void;
     updateDocument: (name: string, aliases: string[]) => boolean;
+    setNameSearchable: (name: string, enabled: boolean) => boolean;
+    updateDocumentsBatch: (updates: DocumentUpdate[]) => BatchUpdateResult;
+    transaction: (ops: TransactionOp[]) => void;
     clearIndex: () => void;
     getVersion: () => number;
+    getDocumentCount: () => number;
+    getDocLen: (name: string) => number | null;
+    getMeta: (name: string) => unknown;
+    setBoosts: (boosts: Record<string, number>) => void;
+    setBoost: (name: string, boost: number) => void;
+    documentsInCategory: (category: string, limit?: number, offset?: number) => string[];
+    docsForAlias: (alias: string) => string[];
+    documentsWithoutAliases: (limit?: number) => string[];
+    documentsOnlyPartiallySearchable: (limit?: number) => string[];
+    fingerprint: () => string;
+    verifyIntegrity: () => IntegrityReport;
+    setKanaConversion: (enabled: boolean) => void;
+    setKanaNormalization: (enabled: boolean) => void;
+    addKanaOverride: (romaji: string, hiragana: string) => void;
+    setNumericExactOnly: (enabled: boolean) => void;
+    setAliasPositionDecay: (enabled: boolean) => void;
+    setPreferExactCase: (enabled: boolean) => void;
+    setTieBreak: (mode: TieBreak) => void;
+    setSuppressPartialsOnExact: (enabled: boolean) => void;
+    setWordStartBonus: (weight: number) => void;
+    setAppendOnly: (enabled: boolean) => void;
+    setMaxLimit: (maxLimit?: number) => void;
+    resetAllSettings: () => void;
+    exportConfig: () => IndexConfig;
+    importConfig: (config: Partial<IndexConfig>) => void;
+    setSearchBudget: (maxOps?: number) => void;
+    setStripChars: (chars: string) => void;
+    setWhitespaceMode: (mode: WhitespaceMode) => void;
+    setLocale: (locale: SearchLocale) => void;
+    enableMatchStats: () => void;
+    matchStats: (limit?: number) => MatchStat[];
+    resetMatchStats: () => void;
 };
 
 function packIndexInstance(index: Index): SearchEngineInstance {
     return {
         addDocuments: (idx: SearchIndex) => index.add_documents(JSON.stringify(idx)),
+        addDocumentsNdjson: (ndjson: string) => index.addDocumentsNdjson(ndjson),
         search: (query: string, limit?: number) => index.search(JSON.stringify([query]), limit),
         searchNoLimit: (query: string) => index.searchNoLimit(JSON.stringify([query])),
         searchWithLimit: (query: string, limit: number) => index.searchWithLimit(JSON.stringify([query]), limit),
+        searchWithLabels: (query: string, limit?: number) => index.searchWithLabels(JSON.stringify([query]), limit),
+        searchWithRomaji: (query: string, limit?: number) => index.searchWithRomaji(JSON.stringify([query]), limit),
+        searchWithCategory: (query: string, limit?: number) => index.searchWithCategory(JSON.stringify([query]), limit),
+        searchWithHighlights: (query: string, limit?: number) => index.searchWithHighlights(JSON.stringify([query]), limit),
+        searchWithBoostTerms: (query: string, boostTerms: string[], weight: number, limit?: number) => index.searchWithBoostTerms(JSON.stringify([query]), JSON.stringify(boostTerms), weight, limit),
+        searchFields: (query: string, fields: SearchField[] = ['name', 'alias'], limit?: number) => index.searchFields(JSON.stringify([query]), JSON.stringify(fields), limit),
+        searchTyped: (query: string, limit?: number) => Promise.resolve(index.searchTyped(JSON.stringify([query]), limit)),
+        searchFuzzyPrefix: (query: string, maxDistance: number, limit?: number) => index.searchFuzzyPrefix(JSON.stringify([query]), maxDistance, limit),
+        searchFuzzyAnd: (query: string, maxDistance: number, limit?: number) => index.searchFuzzyAnd(JSON.stringify([query]), maxDistance, limit),
+        searchMinPriority: (query: string, minPriority: MatchPriorityLabel, limit?: number) => index.searchMinPriority(JSON.stringify([query]), minPriority, limit),
+        searchSorted: (query: string, sortMode: SortMode, limit?: number) => index.searchSorted(JSON.stringify([query]), limit, sortMode),
+        searchInCategories: (query: string, categories: string[], limit?: number) => index.searchInCategories(JSON.stringify([query]), JSON.stringify(categories), limit),
+        searchDiversified: (query: string, maxPerCategory: number, limit?: number) => index.searchDiversified(JSON.stringify([query]), limit, maxPerCategory),
+        searchBinary: (query: string, limit?: number) => Promise.resolve(index.searchBinary(JSON.stringify([query]), limit)),
+        searchByGramOverlap: (query: string, minOverlap: number, limit?: number) => index.searchByGramOverlap(JSON.stringify([query]), minOverlap, limit),
+        searchWithComparator: (query: string, compare: SearchComparator, limit?: number) => index.searchWithComparator(JSON.stringify([query]), limit, compare),
+        countMatches: (query: string) => index.countMatches(JSON.stringify([query])),
+        resultOverlap: (queryA: string, queryB: string, limit?: number) => index.resultOverlap(JSON.stringify([queryA]), JSON.stringify([queryB]), limit),
+        matchedDocumentSet: (query: string) => index.matchedDocumentSet(JSON.stringify([query])),
+        matchDistribution: (query: string) => index.matchDistribution(JSON.stringify([query])),
+        hasAnyMatch: (query: string) => index.hasAnyMatch(JSON.stringify([query])),
+        searchBest: (query: string) => Promise.resolve(index.searchBest(JSON.stringify([query])) ?? null),
+        searchExact: (term: string, limit?: number) => index.searchExact(term, limit),
+        searchByChar: (ch: string) => index.searchByChar(ch),
+        searchSuffix: (suffix: string, limit?: number) => index.searchSuffix(suffix, limit),
+        searchPrefixFast: (query: string, limit?: number) => index.searchPrefixFast(JSON.stringify([query]), limit),
+        buildPrefixIndex: () => index.buildPrefixIndex(),
+        suggest: (query: string, maxSuggestions?: number) => index.suggest(JSON.stringify([query]), maxSuggestions),
         dump: () => index.dump(),
         load: (data: Uint8Array) => {
             const newIndex = Index.load(data);
             Object.assign(index, newIndex);
         },
         removeDocument: (name: string) => index.removeDocument(name),
-        addDocument: (name: string, aliases: string[]) => index.addDocument(name, JSON.stringify(aliases)),
+        retainTop: (k: number) => index.retainTop(k),
+        addDocument: (name: string, aliases: string[], nameSearchable?: boolean, category?: string) => index.addDocument(name, JSON.stringify(aliases), nameSearchable, category),
+        addDocumentWeighted: (name: string, weightedAliases: WeightedAlias[]) => index.addDocumentWeighted(name, JSON.stringify(weightedAliases)),
         updateDocument: (name: string, aliases: string[]) => index.updateDocument(name, JSON.stringify(aliases)),
+        setNameSearchable: (name: string, enabled: boolean) => index.setNameSearchable(name, enabled),
+        updateDocumentsBatch: (updates: DocumentUpdate[]) => index.updateDocumentsBatch(JSON.stringify({ updates })),
+        transaction: (ops: TransactionOp[]) => index.transaction(JSON.stringify(ops)),
         clearIndex: () => index.clearIndex(),
         getVersion: () => index.getVersion(),
+        getDocumentCount: () => index.getDocumentCount(),
+        getDocLen: (name: string) => index.getDocLen(name) ?? null,
+        getMeta: (name: string) => index.getMeta(name),
+        setBoosts: (boosts: Record<string, number>) => index.setBoosts(JSON.stringify(boosts)),
+        setBoost: (name: string, boost: number) => index.setBoost(name, boost),
+        documentsInCategory: (category: string, limit?: number, offset?: number) => index.documentsInCategory(category, limit, offset),
+        docsForAlias: (alias: string) => index.docsForAlias(alias),
+        documentsWithoutAliases: (limit?: number) => index.documentsWithoutAliases(limit),
+        documentsOnlyPartiallySearchable: (limit?: number) => index.documentsOnlyPartiallySearchable(limit),
+        fingerprint: () => index.fingerprint(),
+        verifyIntegrity: () => index.verifyIntegrity(),
+        setKanaConversion: (enabled: boolean) => index.setKanaConversion(enabled),
+        setKanaNormalization: (enabled: boolean) => index.setKanaNormalization(enabled),
+        addKanaOverride: (romaji: string, hiragana: string) => index.addKanaOverride(romaji, hiragana),
+        setNumericExactOnly: (enabled: boolean) => index.setNumericExactOnly(enabled),
+        setAliasPositionDecay: (enabled: boolean) => index.setAliasPositionDecay(enabled),
+        setPreferExactCase: (enabled: boolean) => index.setPreferExactCase(enabled),
+        setTieBreak: (mode: TieBreak) => index.setTieBreak(mode),
+        setSuppressPartialsOnExact: (enabled: boolean) => index.setSuppressPartialsOnExact(enabled),
+        setWordStartBonus: (weight: number) => index.setWordStartBonus(weight),
+        setAppendOnly: (enabled: boolean) => index.setAppendOnly(enabled),
+        setMaxLimit: (maxLimit?: number) => index.setMaxLimit(maxLimit),
+        resetAllSettings: () => index.resetAllSettings(),
+        exportConfig: () => index.exportConfig(),
+        importConfig: (config: Partial<IndexConfig>) => index.importConfig(config),
+        setSearchBudget: (maxOps?: number) => index.setSearchBudget(maxOps),
+        setStripChars: (chars: string) => index.setStripChars(chars),
+        setWhitespaceMode: (mode: WhitespaceMode) => index.setWhitespaceMode(mode),
+        setLocale: (locale: SearchLocale) => index.setLocale(locale),
+        enableMatchStats: () => index.enableMatchStats(),
+        matchStats: (limit?: number) => index.matchStats(limit),
+        resetMatchStats: () => index.resetMatchStats(),
     };
 }
 
+export type BinarySearchResult = {
+    id: string;
+    priority: number;
+};
+
+/**
+ * `searchBinary` の結果を読み込む
+ *
+ * 形式（bincode、リトルエンディアン）: 件数(u64) の後に、各結果の ID のバイト長(u64)・ID(UTF-8)・優先度(u8) が続く
+ */
+export function decodeSearchBinary(data: Uint8Array): BinarySearchResult[] {
+    const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+    const decoder = new TextDecoder();
+    const count = Number(view.getBigUint64(0, true));
+    const results: BinarySearchResult[] = [];
+    let offset = 8;
+    for (let i = 0; i < count; i++) {
+        const length = Number(view.getBigUint64(offset, true));
+        offset += 8;
+        const id = decoder.decode(data.subarray(offset, offset + length));
+        offset += length;
+        results.push({ id, priority: view.getUint8(offset) });
+        offset += 1;
+    }
+    return results;
+}
+
+// 優先度ラベルの並び順（小さいほど上位）
+const MATCH_PRIORITY_ORDER: Record<MatchPriorityLabel, number> = {
+    name_exact: 1,
+    alias_exact: 2,
+    name_prefix: 3,
+    alias_prefix: 4,
+    name_partial: 5,
+    alias_partial: 6,
+    name_fuzzy_prefix: 7,
+    alias_fuzzy_prefix: 8,
+};
+
+/**
+ * 複数のインデックスを横断して検索し、一致の優先度順に1つのリストへまとめる
+ *
+ * 優先度はインデックスごとの統計に依存しないため、そのまま比較できる。
+ * 同じ優先度の結果は `engines` の並び順、同じIDは最も上位の1件のみ残す。
+ */
+export async function searchMany(engines: SearchEngineInstance[], query: string, limit = 10): Promise<LabeledSearchResult[]> {
+    const perIndex = await Promise.all(engines.map((engine) => engine.searchWithLabels(query, limit)));
+
+    // sort は安定なので、同じ優先度ではインデックスの順序と各インデックス内の順位が保たれる
+    const merged = perIndex.flat().sort((a, b) => MATCH_PRIORITY_ORDER[a.priority] - MATCH_PRIORITY_ORDER[b.priority]);
+
+    const seen = new Set<string>();
+    const results: LabeledSearchResult[] = [];
+    for (const result of merged) {
+        if (results.length >= limit) break;
+        if (seen.has(result.id)) continue;
+        seen.add(result.id);
+        results.push(result);
+    }
+    return results;
+}
+
 export async function createSearchEngine(opts?: SearchEngineConfig): Promise<SearchEngineInstance> {
     const _opts = defu<_SearchEngineConfig, SearchEngineConfig[]>(opts, {
         wasmInput: null,
@@ -1,6 +1,6 @@
 import { describe, it, expect } from 'vitest';
-import { createSearchEngine } from '../dist/index.js';
-import type { SearchIndex } from '../dist/index.js';
+import { createSearchEngine, decodeSearchBinary, searchMany } from '../dist/index.js';
+import type { IndexConfig, SearchIndex } from '../dist/index.js';
 
 describe('Search Engine Test', () => {
     // 旧形式のインデックスを模擬するためのヘルパー
@@ -133,7 +133,7 @@ describe('Search Engine Test', () => {
         const engine = await setupTestIndex();
         
         // バージョン確認
-        expect(engine.getVersion()).toBe(2);
+        expect(engine.getVersion()).toBe(4);
         
         // ダンプを作成して再読み込み
         const dump = engine.dump();
@@ -146,8 +146,1595 @@ describe('Search Engine Test', () => {
         expect(results[0]).toBe('smile');
         
         // バージョンが正しいか確認
-        expect(engine2.getVersion()).toBe(2);
+        expect(engine2.getVersion()).toBe(4);
     });
-});
 
+    it('Disable kana conversion', async () => {
+        const engine = await setupTestIndex();
+
+        engine.setKanaConversion(false);
+        let results = await engine.searchWithLimit('egao', 10);
+        expect(results).toHaveLength(0);
+
+        // ローマ字以外の検索には影響しない
+        results = await engine.searchWithLimit('えがお', 10);
+        expect(results).toHaveLength(1);
+        expect(results[0]).toBe('笑顔');
+
+        // ダンプから復元しても設定が維持される
+        const engine2 = await createSearchEngine();
+        engine2.load(engine.dump());
+        results = await engine2.searchWithLimit('egao', 10);
+        expect(results).toHaveLength(0);
+
+        engine2.setKanaConversion(true);
+        results = await engine2.searchWithLimit('egao', 10);
+        expect(results).toHaveLength(1);
+        expect(results[0]).toBe('笑顔');
+    });
+
+    it('Fuzzy prefix search', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat_face', ['neko']);
+        engine.addDocument('cta', ['typo']);
+        engine.addDocument('dog', ['inu']);
+
+        // 完全な前方一致があいまい一致より上位
+        let results = await engine.searchFuzzyPrefix('cta', 1, 10);
+        expect(results).toEqual(['cta', 'cat_face']);
+
+        // 入れ替え1回も1編集として扱う
+        results = await engine.searchFuzzyPrefix('cat', 1, 10);
+        expect(results).toEqual(['cat_face', 'cta']);
+
+        // 3文字未満のクエリはあいまい一致しない
+        results = await engine.searchFuzzyPrefix('ct', 1, 10);
+        expect(results).toEqual(['cta']);
+
+        // 距離0なら通常の前方一致のみ
+        results = await engine.searchFuzzyPrefix('cta', 0, 10);
+        expect(results).toEqual(['cta']);
+    });
+
+    it('Astral-plane characters in names and aliases', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('🐱', ['ねこ']);
+        engine.addDocument('🐱🐶', ['pets']);
+        engine.addDocument('animals', ['🐶🐭🐹']);
+
+        let results = await engine.searchWithLimit('🐱', 10);
+        expect(results).toEqual(['🐱', '🐱🐶']);
+
+        results = await engine.searchWithLimit('🐶', 10);
+        expect(results).toHaveLength(2);
+        expect(results).toContain('🐱🐶');
+        expect(results).toContain('animals');
+
+        results = await engine.searchWithLimit('🐭🐹', 10);
+        expect(results).toEqual(['animals']);
+
+        // 削除後に検索結果へ残らない
+        expect(engine.removeDocument('🐱🐶')).toBe(true);
+        results = await engine.searchWithLimit('🐶', 10);
+        expect(results).toEqual(['animals']);
+        results = await engine.searchWithLimit('pets', 10);
+        expect(results).toHaveLength(0);
+
+        expect(engine.removeDocument('🐱')).toBe(true);
+        results = await engine.searchWithLimit('ねこ', 10);
+        expect(results).toHaveLength(0);
+    });
+
+    it('Alias-only searchable documents', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocuments({
+            emojis: [
+                { name: 'hidden_blobcat', aliases: ['ぶろぶきゃっと'], nameSearchable: false },
+                { name: 'blobcat', aliases: [] },
+            ],
+        });
+
+        let results = await engine.searchWithLimit('hidden_blobcat', 10);
+        expect(results).toHaveLength(0);
+
+        results = await engine.searchWithLimit('blobcat', 10);
+        expect(results).toEqual(['blobcat']);
+
+        results = await engine.searchWithLimit('ぶろぶ', 10);
+        expect(results).toEqual(['hidden_blobcat']);
+
+        // addDocument でも指定でき、更新しても維持される
+        engine.addDocument('secret', ['himitsu'], false);
+        engine.updateDocument('secret', ['ひみつ']);
+        results = await engine.searchWithLimit('secret', 10);
+        expect(results).toHaveLength(0);
+        results = await engine.searchWithLimit('ひみつ', 10);
+        expect(results).toEqual(['secret']);
+
+        // ダンプから復元しても維持される
+        const engine2 = await createSearchEngine();
+        engine2.load(engine.dump());
+        results = await engine2.searchWithLimit('hidden_blobcat', 10);
+        expect(results).toHaveLength(0);
+    });
+
+    it('Count matches', async () => {
+        const engine = await createSearchEngine();
+        for (let i = 0; i < 30; i++) {
+            engine.addDocument(`cat_${i}`, [`neko${i}`]);
+        }
+        engine.addDocument('dog', ['inu']);
+
+        for (const query of ['cat', 'neko', 'cat 1', 'dog', 'none']) {
+            const results = await engine.searchWithLimit(query, 1000);
+            expect(engine.countMatches(query)).toBe(results.length);
+        }
+        expect(engine.countMatches('cat')).toBe(30);
+    });
+
+    it('Exact-only search', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('Cat', ['ネコ', 'kitty']);
+        engine.addDocument('cat_face', ['cat']);
+        engine.addDocument('catalog', ['book']);
+
+        // 名前の完全一致がエイリアスの完全一致より先に並ぶ
+        let results = await engine.searchExact('cat', 10);
+        expect(results).toEqual(['Cat', 'cat_face']);
+
+        // エイリアスの完全一致（カタカナ・ひらがな、全角・半角を同一視）
+        results = await engine.searchExact('ねこ', 10);
+        expect(results).toEqual(['Cat']);
+        results = await engine.searchExact('ＫＩＴＴＹ', 10);
+        expect(results).toEqual(['Cat']);
+
+        // 部分一致は返さない
+        results = await engine.searchExact('cata', 10);
+        expect(results).toHaveLength(0);
+        results = await engine.searchExact('kit', 10);
+        expect(results).toHaveLength(0);
+
+        engine.removeDocument('cat_face');
+        results = await engine.searchExact('cat', 10);
+        expect(results).toEqual(['Cat']);
+    });
+
+    it('Case-insensitive matching across scripts', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('Ω', ['ΩΜΕΓΑ']);
+        engine.addDocument('ΟΔΟΣ', ['road']);
+        engine.addDocument('Кошка', ['КОТ']);
+
+        // ギリシャ文字
+        let results = await engine.searchWithLimit('ω', 10);
+        expect(results[0]).toBe('Ω');
+        results = await engine.searchWithLimit('ωμε', 10);
+        expect(results).toEqual(['Ω']);
+        expect(await engine.searchExact('ωμεγα', 10)).toEqual(['Ω']);
+
+        // 語末のシグマ（ς / σ / Σ）を同一視する
+        results = await engine.searchWithLimit('οδοσ', 10);
+        expect(results).toEqual(['ΟΔΟΣ']);
+        results = await engine.searchWithLimit('οδος', 10);
+        expect(results).toEqual(['ΟΔΟΣ']);
+
+        // キリル文字
+        results = await engine.searchWithLimit('кошка', 10);
+        expect(results).toEqual(['Кошка']);
+        results = await engine.searchWithLimit('КОШ', 10);
+        expect(results).toEqual(['Кошка']);
+        results = await engine.searchWithLimit('кот', 10);
+        expect(results).toEqual(['Кошка']);
+        expect(await engine.searchExact('кот', 10)).toEqual(['Кошка']);
+    });
+
+    it('Batch document updates', async () => {
+        const engine = await setupTestIndex();
+
+        const result = engine.updateDocumentsBatch([
+            { name: 'smile', aliases: ['にこにこ'] },
+            { name: 'unknown', aliases: ['???'] },
+            { name: 'cry', aliases: ['なみだ'] },
+        ]);
+        expect(result.updated).toBe(2);
+        expect(result.missing).toEqual(['unknown']);
+
+        let results = await engine.searchWithLimit('happy', 10);
+        expect(results).toHaveLength(0);
+        results = await engine.searchWithLimit('にこにこ', 10);
+        expect(results).toEqual(['smile']);
+        results = await engine.searchWithLimit('なみだ', 10);
+        expect(results).toEqual(['cry']);
+
+        // 存在しなかったドキュメントは追加されない
+        results = await engine.searchWithLimit('unknown', 10);
+        expect(results).toHaveLength(0);
+    });
+
+    it('Search with priority labels', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', ['neko']);
+        engine.addDocument('catalog', ['book']);
+        engine.addDocument('kitten', ['small_cat']);
+        engine.addDocument('bobcat', []);
+
+        const results = await engine.searchWithLabels('cat', 10);
+        expect(results).toEqual([
+            { id: 'cat', priority: 'name_exact' },
+            { id: 'catalog', priority: 'name_prefix' },
+            { id: 'bobcat', priority: 'name_partial' },
+            { id: 'kitten', priority: 'alias_partial' },
+        ]);
+
+        const aliasResults = await engine.searchWithLabels('neko', 10);
+        expect(aliasResults).toEqual([{ id: 'cat', priority: 'alias_exact' }]);
+    });
+
+    it('Document count stays consistent', async () => {
+        const engine = await createSearchEngine();
+        const expected = new Set<string>();
+
+        engine.addDocuments({
+            emojis: [
+                { name: 'a', aliases: ['x'] },
+                { name: 'b', aliases: [] },
+                { name: 'a', aliases: ['y'] },
+            ],
+        });
+        expected.add('a').add('b');
+        expect(engine.getDocumentCount()).toBe(expected.size);
+
+        engine.addDocument('b', ['z']);
+        engine.addDocument('c', []);
+        expected.add('c');
+        expect(engine.getDocumentCount()).toBe(expected.size);
+
+        engine.updateDocument('c', ['w']);
+        engine.updateDocument('missing', ['w']);
+        expect(engine.getDocumentCount()).toBe(expected.size);
+
+        engine.removeDocument('a');
+        engine.removeDocument('a');
+        expected.delete('a');
+        expect(engine.getDocumentCount()).toBe(expected.size);
+
+        engine.addDocuments({ emojis: [{ name: 'b', aliases: [] }, { name: 'd', aliases: [] }] });
+        expected.add('d');
+        expect(engine.getDocumentCount()).toBe(expected.size);
+
+        const engine2 = await createSearchEngine();
+        engine2.load(engine.dump());
+        expect(engine2.getDocumentCount()).toBe(expected.size);
+
+        engine.clearIndex();
+        expect(engine.getDocumentCount()).toBe(0);
+    });
+
+    it('Typed search results', async () => {
+        const engine = await setupTestIndex();
+
+        const results = await engine.searchTyped('smi', 10);
+        expect(results).toHaveLength(1);
+        expect(results[0].id).toBe('smile');
+        expect(results[0].matchType).toBe('name_prefix');
+        results.forEach((result) => result.free());
+    });
+
+    it('Numeric queries match exactly when enabled', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('100', ['hundred', '満点']);
+        engine.addDocument('1000', []);
+        engine.addDocument('2100', ['year']);
+        engine.addDocument('score', ['100点']);
+
+        // デフォルトでは部分一致も含まれる
+        let results = await engine.searchWithLimit('100', 10);
+        expect(results).toHaveLength(4);
+        expect(results[0]).toBe('100');
+
+        engine.setNumericExactOnly(true);
+        results = await engine.searchWithLimit('100', 10);
+        expect(results).toEqual(['100']);
+
+        // 数字以外のクエリには影響しない
+        results = await engine.searchWithLimit('hun', 10);
+        expect(results).toEqual(['100']);
+    });
+
+    it('Result limit ceiling', async () => {
+        const engine = await createSearchEngine();
+        for (let i = 0; i < 50; i++) {
+            engine.addDocument(`emoji_${i}`, []);
+        }
+
+        engine.setMaxLimit(5);
+        let results = await engine.searchWithLimit('emoji', 0xffffffff);
+        expect(results).toHaveLength(5);
+        results = await engine.search('emoji');
+        expect(results).toHaveLength(5);
+        expect(await engine.searchWithLabels('emoji', 100)).toHaveLength(5);
+
+        // 上限を解除
+        engine.setMaxLimit();
+        results = await engine.searchWithLimit('emoji', 100);
+        expect(results).toHaveLength(50);
+    });
+
+    it('Match statistics', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat_face', ['ねこ', 'kitty']);
+        engine.addDocument('dog_face', ['いぬ']);
+
+        // 有効にするまでは集計されない
+        await engine.search('cat');
+        expect(engine.matchStats()).toEqual([]);
+
+        engine.enableMatchStats();
+        await engine.search('cat');
+        await engine.search('kitty');
+        await engine.searchWithLabels('kit');
+        await engine.search('いぬ');
+        expect(engine.matchStats()).toEqual([
+            { token: 'kitty', hits: 2 },
+            { token: 'cat_face', hits: 1 },
+            { token: 'いぬ', hits: 1 },
+        ]);
+        expect(engine.matchStats(1)).toEqual([{ token: 'kitty', hits: 2 }]);
+
+        engine.resetMatchStats();
+        expect(engine.matchStats()).toEqual([]);
+        await engine.search('dog');
+        expect(engine.matchStats()).toEqual([{ token: 'dog_face', hits: 1 }]);
+
+        engine.resetMatchStats();
+        await engine.searchFuzzyPrefix('dof', 1, 10);
+        expect(engine.matchStats()).toEqual([{ token: 'dog_face', hits: 1 }]);
+
+        engine.resetMatchStats();
+        await engine.searchExact('KITTY', 10);
+        await engine.searchExact('cat_face', 10);
+        expect(engine.matchStats()).toEqual([
+            { token: 'cat_face', hits: 1 },
+            { token: 'kitty', hits: 1 },
+        ]);
+        await engine.searchByChar('いぬ');
+        expect(engine.matchStats()).toContainEqual({ token: 'いぬ', hits: 1 });
+
+        // 並べ替えた後に返した結果だけを数える
+        engine.resetMatchStats();
+        await engine.searchWithComparator('face', (a, _ap, b) => b.localeCompare(a), 1);
+        expect(engine.matchStats()).toEqual([{ token: 'dog_face', hits: 1 }]);
+
+        // 最も重なった名前・エイリアスを数える
+        engine.resetMatchStats();
+        await engine.searchByGramOverlap('kitty', 0.5, 10);
+        expect(engine.matchStats()).toEqual([{ token: 'kitty', hits: 1 }]);
+
+        engine.resetMatchStats();
+        await engine.searchSuffix('tty', 10);
+        expect(engine.matchStats()).toEqual([{ token: 'kitty', hits: 1 }]);
+    });
+
+    it('Search across multiple indexes', async () => {
+        const faces = await createSearchEngine();
+        faces.addDocument('cat_face', ['ねこ']);
+        faces.addDocument('smile', ['happy']);
+        const animals = await createSearchEngine();
+        animals.addDocument('cat', ['ねこ']);
+        animals.addDocument('black_cat', []);
+        animals.addDocument('cat_face', []);
+
+        const results = await searchMany([faces, animals], 'cat', 10);
+        expect(results).toEqual([
+            { id: 'cat', priority: 'name_exact' },
+            { id: 'cat_face', priority: 'name_prefix' },
+            { id: 'black_cat', priority: 'name_partial' },
+        ]);
+
+        expect(await searchMany([faces, animals], 'cat', 2)).toHaveLength(2);
+        expect(await searchMany([], 'cat')).toEqual([]);
+    });
+
+    it('Unconvertible romaji is not expanded to hiragana', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat_face', ['ねこ']);
+        engine.addDocument('xqz_logo', []);
+
+        // 変換できるローマ字はひらがなとしても照合される
+        let results = await engine.searchWithLimit('neko', 10);
+        expect(results).toEqual(['cat_face']);
+
+        // 変換できないローマ字は元の文字列のみで照合される
+        results = await engine.searchWithLimit('xqz', 10);
+        expect(results).toEqual(['xqz_logo']);
+        expect(await engine.searchExact('xqz', 10)).toEqual([]);
+    });
+
+    it('Strip configured characters before matching', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument(':smile:', ['にこにこ']);
+        engine.addDocument('smiley_cat', []);
+
+        engine.setStripChars(':');
+        for (const query of [':smile:', 'smile', ':smile']) {
+            const results = await engine.searchWithLabels(query, 10);
+            expect(results[0]).toEqual({ id: ':smile:', priority: 'name_exact' });
+        }
+        expect(await engine.searchExact('smile', 10)).toEqual([':smile:']);
+
+        // 設定はダンプに含まれる
+        const engine2 = await createSearchEngine({ preCompiledIndex: engine.dump() });
+        expect(await engine2.searchExact(':smile', 10)).toEqual([':smile:']);
+
+        // 無効にするとコロンも照合に使われる
+        engine.setStripChars('');
+        expect(await engine.searchExact('smile', 10)).toEqual([]);
+    });
+
+    it('Custom comparator for final ordering', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('cat_face', []);
+        engine.addDocument('black_cat', []);
+
+        // 通常の順序は優先度順
+        expect(await engine.searchWithLimit('cat', 10)).toEqual(['cat', 'cat_face', 'black_cat']);
+
+        // ID の逆順で並べる
+        let results = await engine.searchWithComparator('cat', (a, _ap, b) => b.localeCompare(a), 10);
+        expect(results).toEqual(['cat_face', 'cat', 'black_cat']);
+
+        // 優先度の低い順に並べ、上位2件だけ返す
+        results = await engine.searchWithComparator('cat', (_a, ap, _b, bp) => bp - ap, 2);
+        expect(results).toEqual(['black_cat', 'cat_face']);
+
+        // 例外や数値以外の戻り値では通常の順序にフォールバックする
+        results = await engine.searchWithComparator('cat', () => { throw new Error('boom'); }, 10);
+        expect(results).toEqual(['cat', 'cat_face', 'black_cat']);
+        results = await engine.searchWithComparator('cat', (() => 'x') as unknown as () => number, 10);
+        expect(results).toEqual(['cat', 'cat_face', 'black_cat']);
+    });
+
+    it('Whitespace handling modes', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('happy_cat', ['cat smile']);
+        engine.addDocument('happycat', []);
+        engine.addDocument('happy cat', []);
+
+        // デフォルト（split）では AND 検索になる
+        let results = await engine.searchWithLimit('happy cat', 10);
+        expect(results).toHaveLength(3);
+
+        // strip では空白を取り除いて照合する
+        engine.setWhitespaceMode('strip');
+        results = await engine.searchWithLimit('happy cat', 10);
+        expect(results.sort()).toEqual(['happy cat', 'happycat']);
+        expect((await engine.searchExact('happycat', 10)).sort()).toEqual(['happy cat', 'happycat']);
+
+        // keep では空白も文字として照合する
+        engine.setWhitespaceMode('keep');
+        results = await engine.searchWithLimit('happy cat', 10);
+        expect(results).toEqual(['happy cat']);
+        results = await engine.searchWithLimit('cat smile', 10);
+        expect(results).toEqual(['happy_cat']);
+
+        expect(() => engine.setWhitespaceMode('tabs' as never)).toThrow();
+    });
+
+    it('Binary search results', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('cat_face', ['ねこ']);
+        engine.addDocument('black_cat', []);
+
+        const data = await engine.searchBinary('cat', 10);
+        expect(data).toBeInstanceOf(Uint8Array);
+        expect(decodeSearchBinary(data)).toEqual([
+            { id: 'cat', priority: 1 },
+            { id: 'cat_face', priority: 3 },
+            { id: 'black_cat', priority: 5 },
+        ]);
+
+        // マルチバイトの ID もそのまま戻せる
+        engine.addDocument('猫', []);
+        expect(decodeSearchBinary(await engine.searchBinary('猫', 10))).toEqual([{ id: '猫', priority: 1 }]);
+        expect(decodeSearchBinary(await engine.searchBinary('nothing', 10))).toEqual([]);
+    });
+
+    it('JSON errors near multibyte characters do not panic', async () => {
+        const engine = await createSearchEngine();
+        const malformed = {
+            emojis: [{ name: '絵文字'.repeat(10), aliases: 'ねこ' as unknown as string[] }],
+        } satisfies SearchIndex;
+
+        // panic（RuntimeError: unreachable）ではなく、通常のエラーとして報告される
+        expect(() => engine.addDocuments(malformed)).toThrow(/JSON parse error/);
+        // 列はバイト位置なので、マルチバイト文字の多い行でもエラー箇所の周辺が切り出される
+        expect(() => engine.addDocuments(malformed)).toThrow(/Context: '[^']*"aliases":"ねこ"/);
+
+        // インデックスは引き続き使用できる
+        engine.addDocument('cat', []);
+        expect(await engine.searchWithLimit('cat', 10)).toEqual(['cat']);
+    });
+
+    it('Fingerprint is independent of insertion order', async () => {
+        const docs = [
+            { name: 'smile', aliases: ['happy', 'joy'] },
+            { name: 'cry', aliases: ['sad'] },
+            { name: '笑顔', aliases: ['えがお'], nameSearchable: false },
+        ];
+        const engine1 = await createSearchEngine();
+        engine1.addDocuments({ emojis: docs });
+        const engine2 = await createSearchEngine();
+        engine2.addDocuments({ emojis: [...docs].reverse() });
+
+        const fingerprint = engine1.fingerprint();
+        expect(fingerprint).toMatch(/^[0-9a-f]{16}$/);
+        expect(engine2.fingerprint()).toBe(fingerprint);
+
+        // ダンプから読み込んでも変わらない
+        const engine3 = await createSearchEngine({ preCompiledIndex: engine1.dump() });
+        expect(engine3.fingerprint()).toBe(fingerprint);
+
+        // 内容が変わればハッシュも変わる
+        engine2.updateDocument('cry', ['sad', 'tear']);
+        expect(engine2.fingerprint()).not.toBe(fingerprint);
+    });
+
+    it('Locale-aware case folding', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('KIRMIZI', []);
+        engine.addDocument('İSTANBUL', []);
+        engine.addDocument('Straße', []);
+
+        // デフォルトでは I は i になる
+        expect(await engine.searchWithLimit('kirmizi', 10)).toEqual(['KIRMIZI']);
+        expect(await engine.searchWithLimit('kırmızı', 10)).toEqual([]);
+
+        // トルコ語では I は ı、İ は i になる
+        engine.setLocale('tr');
+        expect(await engine.searchWithLimit('kırmızı', 10)).toEqual(['KIRMIZI']);
+        expect(await engine.searchWithLimit('kirmizi', 10)).toEqual([]);
+        expect(await engine.searchWithLimit('istanbul', 10)).toEqual(['İSTANBUL']);
+        expect(await engine.searchExact('KIRMIZI', 10)).toEqual(['KIRMIZI']);
+        // 完全一致検索のローマ字変換も同じロケールで小文字化する
+        engine.addDocument('fox', ['きつね']);
+        expect(await engine.searchExact('KİTSUNE', 10)).toEqual(['fox']);
+        expect(await engine.searchExact('KITSUNE', 10)).toEqual(await engine.searchWithLimit('KITSUNE', 10));
+
+        // ドイツ語では ß は ss になる
+        engine.setLocale('de');
+        expect(await engine.searchWithLimit('strasse', 10)).toEqual(['Straße']);
+        expect(await engine.searchWithLimit('STRASSE', 10)).toEqual(['Straße']);
+
+        expect(() => engine.setLocale('fr' as never)).toThrow();
+    });
+
+    it('Transactions apply all operations or none', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy']);
+        engine.addDocument('cry', ['sad']);
+        const before = engine.fingerprint();
+
+        // 3番目の操作が不正なら何も適用されない
+        expect(() => engine.transaction([
+            { op: 'remove', name: 'cry' },
+            { op: 'add', name: 'cat', aliases: ['ねこ'] },
+            { op: 'update', name: 'smile', aliases: 'joy' as unknown as string[] },
+        ])).toThrow();
+        expect(engine.fingerprint()).toBe(before);
+
+        // 存在しないドキュメントの更新も検証で弾かれる
+        expect(() => engine.transaction([
+            { op: 'add', name: 'cat', aliases: [] },
+            { op: 'update', name: 'dog', aliases: ['いぬ'] },
+        ])).toThrow(/dog/);
+        expect(engine.fingerprint()).toBe(before);
+
+        // すべて正しければ順に適用される
+        engine.transaction([
+            { op: 'remove', name: 'cry' },
+            { op: 'add', name: 'cat', aliases: ['ねこ'] },
+            { op: 'update', name: 'cat', aliases: ['にゃん'] },
+            { op: 'update', name: 'smile', aliases: ['joy'] },
+        ]);
+        expect(engine.getDocumentCount()).toBe(2);
+        expect(await engine.searchWithLimit('sad', 10)).toEqual([]);
+        expect(await engine.searchWithLimit('にゃん', 10)).toEqual(['cat']);
+        expect(await engine.searchWithLimit('joy', 10)).toEqual(['smile']);
+    });
+
+    it('List documents in a category', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocuments({
+            emojis: [
+                { name: 'dog', aliases: ['いぬ'], category: 'animal' },
+                { name: 'cat', aliases: ['ねこ'], category: 'animal' },
+                { name: 'bird', aliases: [], category: 'animal' },
+                { name: 'smile', aliases: ['happy'], category: 'face' },
+                { name: 'plain', aliases: [] },
+            ],
+        });
+        engine.addDocument('ant', [], true, 'animal');
+
+        expect(engine.documentsInCategory('animal', 10)).toEqual(['ant', 'bird', 'cat', 'dog']);
+        expect(engine.documentsInCategory('face', 10)).toEqual(['smile']);
+        expect(engine.documentsInCategory('unknown', 10)).toEqual([]);
+
+        // ページング
+        expect(engine.documentsInCategory('animal', 2, 0)).toEqual(['ant', 'bird']);
+        expect(engine.documentsInCategory('animal', 2, 2)).toEqual(['cat', 'dog']);
+        expect(engine.documentsInCategory('animal', 2, 4)).toEqual([]);
+
+        // 更新してもカテゴリは保持され、削除すると一覧から消える
+        engine.updateDocument('cat', ['にゃん']);
+        engine.removeDocument('dog');
+        expect(engine.documentsInCategory('animal', 10)).toEqual(['ant', 'bird', 'cat']);
+
+        // ダンプに含まれる
+        const engine2 = await createSearchEngine({ preCompiledIndex: engine.dump() });
+        expect(engine2.documentsInCategory('animal', 10)).toEqual(['ant', 'bird', 'cat']);
+    });
+
+    it('Search by 2-gram overlap', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('thumbs_up', ['good']);
+        engine.addDocument('thumbsup', []);
+        engine.addDocument('thumbs_down', []);
+        engine.addDocument('smile', []);
+
+        let results = await engine.searchByGramOverlap('thumbs_up', 0.5, 10);
+        expect(results.map((r) => r.id)).toEqual(['thumbs_up', 'thumbsup', 'thumbs_down']);
+        expect(results[0].overlap).toBe(1);
+        expect(results[1].overlap).toBeCloseTo(2 / 3);
+
+        // タイプミスがあっても近いものが残る
+        results = await engine.searchByGramOverlap('thubms_up', 0.4, 10);
+        expect(results).toHaveLength(1);
+        expect(results[0].id).toBe('thumbs_up');
+        expect(results[0].overlap).toBeCloseTo(5 / 11);
+
+        // 語順の入れ替え
+        results = await engine.searchByGramOverlap('up_thumbs', 0.55, 10);
+        expect(results.map((r) => r.id)).toEqual(['thumbsup', 'thumbs_up']);
+
+        // エイリアスも対象になる
+        results = await engine.searchByGramOverlap('goood', 0.5, 10);
+        expect(results.map((r) => r.id)).toEqual(['thumbs_up']);
+    });
+
+    it('Add documents from NDJSON', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocumentsNdjson([
+            '{"name":"smile","aliases":["happy"]}',
+            '',
+            '{"name":"cat","aliases":["ねこ"],"category":"animal"}',
+            '   ',
+            '{"name":"cry","aliases":["sad"]}',
+        ].join('\n'));
+        expect(engine.getDocumentCount()).toBe(3);
+        expect(await engine.searchWithLimit('ねこ', 10)).toEqual(['cat']);
+
+        // 不正な行は行番号付きで報告され、何も追加されない
+        const malformed = [
+            '{"name":"dog","aliases":["いぬ"]}',
+            '{"name":"bird","aliases":["とり"]}',
+            '',
+            '{"name":"fish","aliases":',
+        ].join('\n');
+        expect(() => engine.addDocumentsNdjson(malformed)).toThrow(/Line 4/);
+        expect(engine.getDocumentCount()).toBe(3);
+        expect(await engine.searchWithLimit('いぬ', 10)).toEqual([]);
+    });
+
+    it('Alphabetical result ordering', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('black_cat', []);
+        engine.addDocument('cat_face', []);
+        engine.addDocument('white_cat', ['しろねこ']);
+        engine.addDocument('dog', []);
+
+        expect(await engine.searchSorted('cat', 'relevance', 10)).toEqual(await engine.searchWithLimit('cat', 10));
+
+        // 一致したドキュメントだけが名前順に並ぶ
+        expect(await engine.searchSorted('cat', 'alpha', 10)).toEqual(['black_cat', 'cat', 'cat_face', 'white_cat']);
+        expect(await engine.searchSorted('cat', 'alpha', 2)).toEqual(['black_cat', 'cat']);
+
+        await expect(async () => engine.searchSorted('cat', 'random' as never, 10)).rejects.toThrow();
+    });
+
+    it('Case-variant duplicate aliases are stored once', async () => {
+        const engine1 = await createSearchEngine();
+        engine1.addDocument('cat', ['cat', 'CAT', 'ｃａｔ', 'ネコ', 'ねこ', 'kitty']);
+        const engine2 = await createSearchEngine();
+        engine2.addDocument('cat', ['cat', 'ネコ', 'kitty']);
+
+        // 最初に現れた表記だけが残る
+        expect(engine1.fingerprint()).toBe(engine2.fingerprint());
+
+        engine1.addDocuments({ emojis: [{ name: 'dog', aliases: ['Dog', 'DOG', 'いぬ'] }] });
+        engine2.addDocuments({ emojis: [{ name: 'dog', aliases: ['Dog', 'いぬ'] }] });
+        expect(engine1.fingerprint()).toBe(engine2.fingerprint());
+
+        // 検索結果は変わらない
+        expect(await engine1.searchExact('CAT', 10)).toEqual(['cat']);
+        expect(await engine1.searchWithLimit('ねこ', 10)).toEqual(['cat']);
+    });
+
+    it('Exclude matches below a priority floor', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('cat_face', []);
+        engine.addDocument('kitty', ['cat_like']);
+        engine.addDocument('black_cat', []);
+
+        expect(await engine.searchWithLimit('cat', 10)).toHaveLength(4);
+
+        // 名前の前方一致までに制限すると、エイリアスの前方一致と部分一致は除外される
+        let results = await engine.searchMinPriority('cat', 'name_prefix', 10);
+        expect(results).toEqual(['cat', 'cat_face']);
+
+        results = await engine.searchMinPriority('cat', 'alias_prefix', 10);
+        expect(results).toEqual(['cat', 'cat_face', 'kitty']);
+
+        await expect(async () => engine.searchMinPriority('cat', 'best' as never, 10)).rejects.toThrow();
+    });
+
+    it('Bulk boosts reorder results within a match tier', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('cat_face', []);
+        engine.addDocument('cat_paw', []);
+        engine.addDocument('cat_tail', []);
+
+        engine.setBoosts({ cat_tail: 100, cat_paw: 50, cat: 1, unknown_doc: 10 });
+        let results = await engine.searchWithLimit('cat', 10);
+        // 完全一致は前方一致より常に上位
+        expect(results).toEqual(['cat', 'cat_tail', 'cat_paw', 'cat_face']);
+
+        results = await engine.searchWithLimit('cat_', 2);
+        expect(results).toEqual(['cat_tail', 'cat_paw']);
+
+        // 存在しないドキュメントのブーストは追加時に適用される
+        engine.addDocument('unknown_doc', ['cat_unknown']);
+        results = await engine.searchWithLimit('cat_', 10);
+        expect(results).toEqual(['cat_tail', 'cat_paw', 'cat_face', 'unknown_doc']);
+
+        // 置き換え
+        engine.setBoosts({ cat_face: 5 });
+        results = await engine.searchWithLimit('cat_', 1);
+        expect(results).toEqual(['cat_face']);
+    });
+
+    it('Toggle name searchability without touching aliases', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('blobcat', ['blobneko', 'ぶろぶ']);
+
+        expect(engine.setNameSearchable('blobcat', false)).toBe(true);
+        expect(await engine.searchWithLimit('blobcat', 10)).toEqual([]);
+        expect(await engine.searchExact('blobcat', 10)).toEqual([]);
+        // 名前と共通する部分を持つエイリアスでも検索できる
+        expect(await engine.searchWithLimit('blob', 10)).toEqual(['blobcat']);
+        expect(await engine.searchWithLimit('ぶろぶ', 10)).toEqual(['blobcat']);
+
+        expect(engine.setNameSearchable('blobcat', true)).toBe(true);
+        expect(await engine.searchWithLabels('blobcat', 10)).toEqual([{ id: 'blobcat', priority: 'name_exact' }]);
+        expect(await engine.searchWithLimit('blobneko', 10)).toEqual(['blobcat']);
 
+        expect(engine.setNameSearchable('missing', false)).toBe(false);
+    });
+
+    it('Check whether a query matches anything', async () => {
+        const engine = await createSearchEngine();
+        expect(engine.hasAnyMatch('cat')).toBe(false);
+
+        engine.addDocument('cat_face', ['ねこ']);
+        engine.addDocument('dog', []);
+
+        expect(engine.hasAnyMatch('cat')).toBe(true);
+        expect(engine.hasAnyMatch('neko')).toBe(true);
+        expect(engine.hasAnyMatch('face cat')).toBe(true);
+        expect(engine.hasAnyMatch('fish')).toBe(false);
+        expect(engine.hasAnyMatch('face dog')).toBe(false);
+    });
+
+    it('Full unranked set of matching documents', async () => {
+        const engine = await createSearchEngine();
+        for (let i = 0; i < 40; i++) {
+            engine.addDocument(`cat_${i}`, []);
+        }
+        engine.addDocument('dog', ['ねこ嫌い']);
+        engine.addDocument('kitten', ['cat']);
+
+        const ids = engine.matchedDocumentSet('cat');
+        // 件数の上限なしで、重複なく返る
+        expect(ids).toHaveLength(41);
+        expect(new Set(ids).size).toBe(ids.length);
+        expect(ids.length).toBe(engine.countMatches('cat'));
+        expect(ids).toContain('kitten');
+        expect(ids).not.toContain('dog');
+
+        expect(engine.matchedDocumentSet('cat 1').length).toBe(engine.countMatches('cat 1'));
+        expect(engine.matchedDocumentSet('fish')).toEqual([]);
+    });
+
+    it('Earlier aliases rank higher when position decay is enabled', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('emoji_a', ['grin', 'laugh', 'smile', 'joy', 'happy']);
+        engine.addDocument('emoji_b', ['happy', 'grin', 'laugh']);
+        engine.addDocument('emoji_c', ['laugh', 'happy']);
+
+        engine.setAliasPositionDecay(true);
+        expect(await engine.searchWithLimit('happy', 10)).toEqual(['emoji_b', 'emoji_c', 'emoji_a']);
+        expect(await engine.searchWithLimit('laugh', 10)).toEqual(['emoji_c', 'emoji_a', 'emoji_b']);
+
+        // 複数のエイリアスに一致する場合は最も前の位置が使われる
+        expect(await engine.searchWithLimit('g', 10)).toEqual(['emoji_a', 'emoji_b', 'emoji_c']);
+
+        // 一致の種類の順位は変わらない
+        engine.addDocument('happy', []);
+        expect((await engine.searchWithLimit('happy', 10))[0]).toBe('happy');
+    });
+
+    it('Negative boosts demote documents within their tier', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('cat_a', []);
+        engine.addDocument('cat_b', []);
+        engine.addDocument('cat_c', []);
+
+        engine.setBoost('cat_a', 10);
+        expect((await engine.searchWithLimit('cat_', 10))[0]).toBe('cat_a');
+
+        // 負のブーストでブーストなしのドキュメントより下がる
+        engine.setBoost('cat_a', -5);
+        let results = await engine.searchWithLimit('cat_', 10);
+        expect(results).toHaveLength(3);
+        expect(results[2]).toBe('cat_a');
+
+        // 一致の種類をまたいでは下がらない
+        engine.setBoost('cat', -100);
+        results = await engine.searchWithLimit('cat', 10);
+        expect(results[0]).toBe('cat');
+        expect(results[3]).toBe('cat_a');
+
+        expect(() => engine.setBoost('cat_b', NaN)).toThrow();
+        expect(() => engine.setBoost('cat_b', Infinity)).toThrow();
+    });
+
+    it('Tie break keeps insertion order of equal results', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('dog_c', []);
+        engine.addDocument('dog_a', []);
+        engine.addDocument('dog_b', []);
+
+        engine.setTieBreak('insertion');
+        expect(await engine.searchWithLimit('dog_', 10)).toEqual(['dog_c', 'dog_a', 'dog_b']);
+
+        // 更新しても順序は変わらない
+        engine.updateDocument('dog_c', ['puppy']);
+        expect(await engine.searchWithLimit('dog_', 10)).toEqual(['dog_c', 'dog_a', 'dog_b']);
+
+        // 追加された順序は dump / load で引き継がれる
+        const restored = await createSearchEngine();
+        restored.load(engine.dump());
+        expect(await restored.searchWithLimit('dog_', 10)).toEqual(['dog_c', 'dog_a', 'dog_b']);
+
+        engine.setTieBreak('lexicographic');
+        expect(await engine.searchWithLimit('dog_', 10)).toEqual(['dog_a', 'dog_b', 'dog_c']);
+
+        expect(() => engine.setTieBreak('random' as never)).toThrow();
+    });
+
+    it('Finds documents by exact alias', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy', 'grin']);
+        engine.addDocument('joy', ['Happy', 'tears']);
+        engine.addDocument('happy_face', ['face']);
+
+        // 同じエイリアスを持つドキュメントがすべて返る
+        expect(engine.docsForAlias('happy')).toEqual(['joy', 'smile']);
+        expect(engine.docsForAlias('HAPPY')).toEqual(['joy', 'smile']);
+        expect(engine.docsForAlias('grin')).toEqual(['smile']);
+        expect(engine.docsForAlias('hap')).toEqual([]);
+
+        engine.updateDocument('joy', ['tears']);
+        expect(engine.docsForAlias('happy')).toEqual(['smile']);
+
+        engine.removeDocument('smile');
+        expect(engine.docsForAlias('happy')).toEqual([]);
+    });
+
+    it('Search budget returns partial results early', async () => {
+        const engine = await createSearchEngine();
+        for (let i = 0; i < 100; i++) {
+            engine.addDocument(`item_${i}`, []);
+        }
+
+        engine.setSearchBudget(5);
+        const partial = await engine.searchWithLimit('item', 50);
+        expect(partial.length).toBeGreaterThan(0);
+        expect(partial.length).toBeLessThanOrEqual(5);
+
+        engine.setSearchBudget();
+        expect(await engine.searchWithLimit('item', 50)).toHaveLength(50);
+    });
+
+    it('Filters search results by any of several categories', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('star_animal', [], true, 'animal');
+        engine.addDocument('star_food', [], true, 'food');
+        engine.addDocument('star_flag', [], true, 'flag');
+        engine.addDocument('star', []);
+
+        const results = await engine.searchInCategories('star', ['animal', 'food'], 10);
+        expect(results.sort()).toEqual(['star_animal', 'star_food']);
+
+        // 空の配列では絞り込まない
+        expect(await engine.searchInCategories('star', [], 10)).toHaveLength(4);
+    });
+
+    it('Finds documents by raw emoji character', async () => {
+        const engine = await createSearchEngine();
+        const family = '\u{1F468}\u200D\u{1F469}\u200D\u{1F467}';
+        engine.addDocument('family', [family]);
+        engine.addDocument('man', ['\u{1F468}']);
+        engine.addDocument('\u{1F431}', ['cat']);
+
+        expect(await engine.searchByChar(family)).toEqual(['family']);
+        // ZWJ 列の一部だけでは一致しない
+        expect(await engine.searchByChar('\u{1F468}')).toEqual(['man']);
+        expect(await engine.searchByChar(' \u{1F431} ')).toEqual(['\u{1F431}']);
+        expect(await engine.searchByChar('\u{1F436}')).toEqual([]);
+    });
+
+    it('Word start bonus ranks matches at word boundaries higher', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('bluecat', []);
+        engine.addDocument('happy_cat', []);
+        engine.addDocument('cat_happy', []);
+
+        engine.setWordStartBonus(1);
+        expect(await engine.searchWithLimit('cat', 10)).toEqual(['cat_happy', 'happy_cat', 'bluecat']);
+
+        // ブーストと加算される
+        engine.setBoost('bluecat', 2);
+        expect(await engine.searchWithLimit('cat', 10)).toEqual(['cat_happy', 'bluecat', 'happy_cat']);
+
+        expect(() => engine.setWordStartBonus(NaN)).toThrow();
+    });
+
+    it('Retains only the top boosted documents', async () => {
+        const engine = await createSearchEngine();
+        for (let i = 0; i < 100; i++) {
+            const name = `emoji_${String(i).padStart(3, '0')}`;
+            engine.addDocument(name, []);
+            engine.setBoost(name, i % 50);
+        }
+
+        expect(engine.retainTop(10)).toBe(90);
+        expect(engine.getDocumentCount()).toBe(10);
+
+        // ブースト 49〜45 の各2件が残る
+        const kept = await engine.searchWithLimit('emoji', 100);
+        const expected = [45, 46, 47, 48, 49].flatMap(i => [i, i + 50])
+            .map(i => `emoji_${String(i).padStart(3, '0')}`);
+        expect(kept.sort()).toEqual(expected.sort());
+
+        // 同じブーストは名前順で選ばれる
+        engine.retainTop(1);
+        expect(await engine.searchWithLimit('emoji', 10)).toEqual(['emoji_049']);
+    });
+
+    it('Reports the match type distribution of a query', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('dog', ['cat']);
+        engine.addDocument('catfish', []);
+        engine.addDocument('catnap', []);
+        engine.addDocument('fish', ['catnip']);
+        engine.addDocument('bobcat', []);
+        engine.addDocument('lion', ['wildcat']);
+        engine.addDocument('mouse', ['cheese']);
+
+        expect(engine.matchDistribution('cat')).toEqual({
+            nameExact: 1,
+            aliasExact: 1,
+            namePrefix: 2,
+            aliasPrefix: 1,
+            namePartial: 1,
+            aliasPartial: 1,
+        });
+        expect(engine.countMatches('cat')).toBe(7);
+    });
+
+    it('Append-only mode rejects duplicate document names', async () => {
+        const engine = await createSearchEngine();
+        engine.setAppendOnly(true);
+        engine.addDocument('cat', ['neko']);
+
+        expect(() => engine.addDocument('cat', ['kitty'])).toThrow(/cat/);
+        expect(engine.docsForAlias('neko')).toEqual(['cat']);
+
+        // 一括追加内の重複もエラーになり、何も追加されない
+        expect(() => engine.addDocuments({ emojis: [
+            { name: 'dog', aliases: [] },
+            { name: 'dog', aliases: ['inu'] },
+        ] })).toThrow();
+        expect(engine.getDocumentCount()).toBe(1);
+
+        engine.setAppendOnly(false);
+        engine.addDocument('cat', ['kitty']);
+        expect(engine.docsForAlias('kitty')).toEqual(['cat']);
+    });
+
+    it('Annotates results with romaji', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('ねこ', ['cat']);
+        engine.addDocument('cat_face', []);
+
+        const results = await engine.searchWithRomaji('cat', 10);
+        expect(results).toHaveLength(2);
+        expect(results).toContainEqual({ id: 'ねこ', romaji: 'neko' });
+        expect(results).toContainEqual({ id: 'cat_face', romaji: 'cat_face' });
+    });
+
+    it('Boost terms reorder results without adding new ones', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('heart_blue', ['blue']);
+        engine.addDocument('heart_red', ['red']);
+        engine.addDocument('heart_green', ['green']);
+        engine.addDocument('apple', ['red', 'fruit']);
+
+        const results = await engine.searchWithBoostTerms('heart', ['red'], 1, 10);
+        expect(results).toHaveLength(3);
+        expect(results[0]).toBe('heart_red');
+        // ブースト語だけに一致するドキュメントは含まれない
+        expect(results).not.toContain('apple');
+
+        await expect(async () => engine.searchWithBoostTerms('heart', ['red'], NaN, 10)).rejects.toThrow();
+    });
+
+    it('Restricts matching to the selected fields', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', ['neko']);
+        engine.addDocument('kitty', ['cat_face']);
+        engine.addDocument('dog', ['inu']);
+
+        expect((await engine.searchFields('cat', ['name'], 10)).sort()).toEqual(['cat']);
+        expect((await engine.searchFields('cat', ['alias'], 10)).sort()).toEqual(['kitty']);
+        expect((await engine.searchFields('cat', ['name', 'alias'], 10)).sort()).toEqual(['cat', 'kitty']);
+        expect((await engine.searchFields('cat', [], 10)).sort()).toEqual(['cat', 'kitty']);
+        expect(await engine.searchFields('neko', ['name'], 10)).toEqual([]);
+
+        await expect(async () => engine.searchFields('cat', ['tags' as never], 10)).rejects.toThrow();
+    });
+
+    it('Finds documents by suffix', async () => {
+        const engine = await createSearchEngine();
+        const names = ['blackcat', 'cat', 'catfish', 'bobcat', 'dog'];
+        for (const name of names) {
+            engine.addDocument(name, []);
+        }
+        engine.addDocument('kitty', ['wildcat']);
+
+        const results = await engine.searchSuffix('cat', 10);
+        expect(results[0]).toBe('cat');
+        // 総当たりの ends_with と同じ集合になる
+        const expected = [...names.filter(name => name.endsWith('cat')), 'kitty'];
+        expect([...results].sort()).toEqual(expected.sort());
+        expect((await engine.searchSuffix('CAT', 10)).sort()).toEqual(expected.sort());
+    });
+
+    it('Index stays consistent across mutations', async () => {
+        const engine = await createSearchEngine();
+        expect(engine.verifyIntegrity()).toEqual({ ok: true, violations: [] });
+
+        for (let i = 0; i < 50; i++) {
+            engine.addDocument(`doc_${i}`, [`alias_${i}`, `shared`], i % 3 !== 0, i % 2 ? 'odd' : 'even');
+        }
+        for (let i = 0; i < 50; i += 4) {
+            engine.updateDocument(`doc_${i}`, [`renamed_${i}`]);
+        }
+        for (let i = 0; i < 50; i += 5) {
+            engine.removeDocument(`doc_${i}`);
+        }
+        engine.setNameSearchable('doc_1', false);
+        engine.retainTop(20);
+        expect(engine.verifyIntegrity()).toEqual({ ok: true, violations: [] });
+
+        const restored = await createSearchEngine();
+        restored.load(engine.dump());
+        expect(restored.verifyIntegrity()).toEqual({ ok: true, violations: [] });
+    });
+
+    it('Suggests terms close to a misspelled query', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy']);
+        engine.addDocument('grin', ['smile']);
+        engine.addDocument('simile', []);
+        engine.addDocument('cat', []);
+
+        const suggestions = engine.suggest('smille');
+        // より多くのドキュメントが持つ語が先に並ぶ
+        expect(suggestions[0]).toBe('smile');
+        expect(suggestions).toContain('simile');
+        expect(suggestions).not.toContain('cat');
+
+        // 存在する語や短い語には候補を返さない
+        expect(engine.suggest('smile')).toEqual([]);
+        expect(engine.suggest('ct')).toEqual([]);
+    });
+
+    it('Diversifies results across categories', async () => {
+        const engine = await createSearchEngine();
+        for (const name of ['star_a1', 'star_a2', 'star_a3']) {
+            engine.addDocument(name, [], true, 'animal');
+            engine.setBoost(name, 10);
+        }
+        engine.addDocument('star_b1', [], true, 'food');
+        engine.addDocument('star_b2', [], true, 'food');
+        engine.addDocument('star_c1', [], true, 'flag');
+        engine.addDocument('star_x', []);
+
+        // 通常の検索では1つのカテゴリが上位を占める
+        const plain = await engine.searchWithLimit('star_', 3);
+        expect(plain.every(id => id.startsWith('star_a'))).toBe(true);
+
+        const results = await engine.searchDiversified('star_', 1, 10);
+        expect(results).toHaveLength(4);
+        expect(results.filter(id => id.startsWith('star_a'))).toHaveLength(1);
+        expect(results.filter(id => id.startsWith('star_b'))).toHaveLength(1);
+        expect(results).toContain('star_c1');
+        // カテゴリのないドキュメントは制限されない
+        expect(results).toContain('star_x');
+    });
+
+    it('Kana normalization matches long vowel and small kana variants', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('noodle', ['ラーメン']);
+        engine.addDocument('little', ['ちょっと']);
+
+        // デフォルトでは表記ゆれを区別する
+        expect(await engine.searchWithLimit('らあめん', 10)).toEqual([]);
+        expect(await engine.searchWithLimit('ramen', 10)).toEqual([]);
+
+        engine.setKanaNormalization(true);
+        expect(await engine.searchWithLimit('らあめん', 10)).toEqual(['noodle']);
+        expect(await engine.searchWithLimit('らーめん', 10)).toEqual(['noodle']);
+        expect(await engine.searchWithLimit('ramen', 10)).toEqual(['noodle']);
+        expect(await engine.searchWithLimit('チヨツト', 10)).toEqual(['little']);
+        expect(await engine.searchWithLimit('ちよっと', 10)).toEqual(['little']);
+
+        // 完全一致検索も表記ゆれをまとめ、設定を戻すと区別する
+        expect(await engine.searchExact('らあめん', 10)).toEqual(['noodle']);
+        expect(await engine.searchExact('ramen', 10)).toEqual(['noodle']);
+        expect(await engine.searchExact('チヨツト', 10)).toEqual(['little']);
+        expect(engine.verifyIntegrity().ok).toBe(true);
+        engine.setKanaNormalization(false);
+        expect(await engine.searchExact('らあめん', 10)).toEqual([]);
+        expect(await engine.searchExact('らーめん', 10)).toEqual(['noodle']);
+        expect(engine.verifyIntegrity().ok).toBe(true);
+    });
+
+    it('searchBest returns the top result of a full search', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy', 'grin']);
+        engine.addDocument('smiley', ['face']);
+        engine.addDocument('grinning', ['smile_big']);
+        engine.addDocument('cat_face', ['neko']);
+        engine.addDocument('happy_cat', ['smile_cat']);
+        engine.setTieBreak('lexicographic');
+
+        for (const query of ['smile', 'smi', 'grin', 'face', 'cat', 'neko', 'happy cat']) {
+            const full = await engine.searchWithLimit(query, 100);
+            expect(await engine.searchBest(query)).toBe(full[0]);
+        }
+        expect(await engine.searchBest('zzz')).toBeNull();
+    });
+
+    it('Kana overrides take precedence over default romaji conversion', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('ocean', ['うみ']);
+
+        // 標準の変換では "uumi" は "ううみ" になり一致しない
+        expect(await engine.searchWithLimit('uumi', 10)).toEqual([]);
+
+        engine.addKanaOverride('uu', 'う');
+        expect(await engine.searchWithLimit('uumi', 10)).toEqual(['ocean']);
+
+        // dump / load で引き継がれる
+        const restored = await createSearchEngine();
+        restored.load(engine.dump());
+        expect(await restored.searchWithLimit('uumi', 10)).toEqual(['ocean']);
+
+        expect(() => engine.addKanaOverride('', 'う')).toThrow();
+    });
+
+    it('Lists documents without aliases', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy', 'joy']);
+        engine.addDocument('zzz', []);
+        engine.addDocument('blank', []);
+        engine.addDocument('cat', ['neko']);
+
+        expect(engine.documentsWithoutAliases()).toEqual(['blank', 'zzz']);
+        expect(engine.documentsWithoutAliases(1)).toEqual(['blank']);
+
+        engine.updateDocument('zzz', ['sleep']);
+        expect(engine.documentsWithoutAliases()).toEqual(['blank']);
+    });
+
+    it('Returns the document length as alias count plus one', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy', 'joy']);
+        engine.addDocument('zzz', []);
+
+        expect(engine.getDocLen('smile')).toBe(3);
+        expect(engine.getDocLen('zzz')).toBe(1);
+        expect(engine.getDocLen('unknown')).toBeNull();
+
+        engine.updateDocument('smile', ['happy']);
+        expect(engine.getDocLen('smile')).toBe(2);
+    });
+
+    it('Serializes result objects with camelCase keys', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', ['neko']);
+
+        const isCamelCase = (key: string) => /^[a-z][a-zA-Z]*$/.test(key);
+        const results: object[] = [
+            engine.matchDistribution('cat'),
+            engine.verifyIntegrity(),
+            engine.updateDocumentsBatch([{ name: 'cat', aliases: ['neko'] }]),
+            ...(await engine.searchWithLabels('cat', 10)),
+        ];
+        for (const result of results) {
+            for (const key of Object.keys(result)) {
+                expect(isCamelCase(key)).toBe(true);
+            }
+        }
+        expect(Object.keys(engine.matchDistribution('cat'))).toContain('nameExact');
+    });
+
+    it('Early exit does not drop a higher-priority match found late', async () => {
+        const engine = await createSearchEngine();
+        const index: Record<string, string[]> = {};
+        for (let i = 0; i < 200; i++) {
+            index[`cat${i}`] = [];
+        }
+        index['cat'] = [];
+        engine.addDocuments(index);
+
+        // 前方一致の候補が多数あっても、名前の完全一致が必ず先頭に来る
+        expect(await engine.searchWithLimit('cat', 1)).toEqual(['cat']);
+        expect((await engine.searchWithLimit('cat', 3))[0]).toBe('cat');
+    });
+
+    it('Indexed prefix search matches the scan-based prefix search', async () => {
+        const engine = await setupTestIndex();
+        engine.addDocument('cathedral', ['church']);
+        engine.addDocument('caterpillar', ['いもむし']);
+        engine.addDocument('hidden_cat', ['catlike'], false);
+        engine.setTieBreak('lexicographic');
+
+        for (const query of ['cat', 'c', 'ne', 'imo', 'hidden', 'smile', 'xyz']) {
+            const scanned = await engine.searchMinPriority(query, 'alias_prefix', 1000);
+            expect(await engine.searchPrefixFast(query, 1000)).toEqual(scanned);
+        }
+
+        // 変更後は索引が作り直される
+        engine.buildPrefixIndex();
+        engine.addDocument('catnap', []);
+        engine.removeDocument('cathedral');
+        expect(await engine.searchPrefixFast('cat', 1000)).toContain('catnap');
+        expect(await engine.searchPrefixFast('cat', 1000)).not.toContain('cathedral');
+        expect(await engine.searchPrefixFast('cat', 1000)).toEqual(await engine.searchMinPriority('cat', 'alias_prefix', 1000));
+
+        // 空白を含む検索語は AND 検索に分割せず、空白も含めて前方一致を調べる
+        engine.addDocument('black cat', []);
+        expect(await engine.searchPrefixFast('black c', 10)).toEqual(['black cat']);
+        expect(await engine.searchMinPriority('black c', 'alias_prefix', 10)).toEqual([]);
+    });
+
+    it('Suppresses partial matches only when an exact match exists', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('catfish', []);
+        engine.addDocument('bobcat', []);
+        engine.addDocument('kitten', ['cathy']);
+        engine.setSuppressPartialsOnExact(true);
+
+        // 完全一致があるので部分一致の bobcat は除かれ、前方一致は残る
+        const results = await engine.searchWithLimit('cat', 10);
+        expect(results[0]).toBe('cat');
+        expect(results.slice(1).sort()).toEqual(['catfish', 'kitten']);
+
+        // 前方一致だけでは部分一致は除かれない
+        expect((await engine.searchWithLimit('catf', 10))).toEqual(['catfish']);
+        expect((await engine.searchWithLimit('ca', 10)).sort()).toEqual(['bobcat', 'cat', 'catfish', 'kitten']);
+
+        engine.setSuppressPartialsOnExact(false);
+        expect((await engine.searchWithLimit('cat', 10)).sort()).toEqual(['bobcat', 'cat', 'catfish', 'kitten']);
+    });
+
+    it('Prefers exact-cased matches among exact matches', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat', []);
+        engine.addDocument('Cat', []);
+        engine.addDocument('CAT', []);
+        engine.addDocument('kitten', ['Neko']);
+        engine.addDocument('kitty', ['neko']);
+        engine.setBoost('cat', 10);
+        engine.setBoost('kitty', 10);
+        engine.setPreferExactCase(true);
+
+        // ブーストより大文字・小文字の一致が優先される
+        expect((await engine.searchWithLimit('Cat', 3))[0]).toBe('Cat');
+        expect((await engine.searchWithLimit('CAT', 3))[0]).toBe('CAT');
+        expect(await engine.searchWithLimit('Neko', 2)).toEqual(['kitten', 'kitty']);
+        // 大文字・小文字が違っても一致はする
+        expect((await engine.searchWithLimit('cAT', 3)).sort()).toEqual(['CAT', 'Cat', 'cat']);
+
+        engine.setPreferExactCase(false);
+        expect((await engine.searchWithLimit('Cat', 3))[0]).toBe('cat');
+
+        // 取り除く文字を設定していても、取り除いた後の形で大文字・小文字を比べる
+        const shortcodes = await createSearchEngine();
+        shortcodes.addDocument(':blob:', []);
+        shortcodes.addDocument(':Blob:', []);
+        shortcodes.setStripChars(':');
+        shortcodes.setPreferExactCase(true);
+        expect((await shortcodes.searchWithLimit('Blob', 2))[0]).toBe(':Blob:');
+        expect((await shortcodes.searchWithLimit(':Blob:', 2))[0]).toBe(':Blob:');
+        expect((await shortcodes.searchWithLimit('blob', 2))[0]).toBe(':blob:');
+    });
+
+    it('Stores and returns document metadata', async () => {
+        const engine = await createSearchEngine();
+        const meta = { license: 'CC BY 4.0', author: { name: 'hana', url: 'https://example.com' }, tags: ['a', 'b'] };
+        engine.addDocuments({
+            emojis: [
+                { name: 'blobcat', aliases: ['cat'], meta },
+                { name: 'smile', aliases: ['happy'] },
+            ],
+        });
+
+        expect(engine.getMeta('blobcat')).toEqual(meta);
+        expect(engine.getMeta('smile')).toBeNull();
+        expect(engine.getMeta('unknown')).toBeNull();
+
+        // メタデータは検索されない
+        expect(await engine.searchWithLimit('hana', 10)).toEqual([]);
+
+        engine.updateDocument('blobcat', ['neko']);
+        expect(engine.getMeta('blobcat')).toEqual(meta);
+
+        const restored = await createSearchEngine();
+        restored.load(engine.dump());
+        expect(restored.getMeta('blobcat')).toEqual(meta);
+
+        engine.removeDocument('blobcat');
+        expect(engine.getMeta('blobcat')).toBeNull();
+        expect(engine.verifyIntegrity().ok).toBe(true);
+    });
+
+    it('Attaches categories to ranked results', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocuments({
+            emojis: [
+                { name: 'cat', aliases: [], category: 'animal' },
+                { name: 'catfish', aliases: [], category: 'fish' },
+                { name: 'bobcat', aliases: [] },
+            ],
+        });
+        engine.setTieBreak('lexicographic');
+
+        const ids = await engine.searchWithLimit('cat', 10);
+        const results = await engine.searchWithCategory('cat', 10);
+        expect(results.map((r) => r.id)).toEqual(ids);
+        expect(results).toEqual([
+            { id: 'cat', category: 'animal' },
+            { id: 'catfish', category: 'fish' },
+            { id: 'bobcat', category: null },
+        ]);
+    });
+
+    it('Fuzzy AND search tolerates a typo in one of several words', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('happy_cat', []);
+        engine.addDocument('happy_dog', []);
+        engine.addDocument('sad_cat', []);
+        engine.addDocument('smile', ['happy', 'face']);
+
+        // 通常の AND 検索ではタイプミスのある語が一致しない
+        expect(await engine.searchWithLimit('hapy cat', 10)).toEqual([]);
+
+        expect(await engine.searchFuzzyAnd('hapy cat', 1, 10)).toEqual(['happy_cat']);
+        expect(await engine.searchFuzzyAnd('hapy face', 1, 10)).toEqual(['smile']);
+        // タイプミスのない一致が上位に並ぶ
+        expect((await engine.searchFuzzyAnd('happy cat', 1, 10))[0]).toBe('happy_cat');
+        // 距離 0 ではタイプミスを許容しない
+        expect(await engine.searchFuzzyAnd('hapy cat', 0, 10)).toEqual([]);
+    });
+
+    it('Exports and imports only the configuration', async () => {
+        const tuned = await createSearchEngine();
+        tuned.addDocument('tiny', []);
+        tuned.setTieBreak('lexicographic');
+        tuned.setStripChars(':');
+        tuned.setWordStartBonus(2);
+        tuned.addKanaOverride('uu', 'う');
+        const config = tuned.exportConfig();
+        // キーは他の戻り値と同じく camelCase
+        expect(config.tieBreak).toBe('lexicographic');
+        expect(config.wordStartBonus).toBe(2);
+        expect(config.kanaOverrides).toEqual({ uu: 'う' });
+
+        const production = await setupTestIndex();
+        production.importConfig(config);
+        expect(production.exportConfig()).toEqual(config);
+        expect(production.getDocumentCount()).toBe(3);
+        expect(production.getDocLen('tiny')).toBeNull();
+        expect(await production.searchWithLimit(':smile:', 10)).toEqual(['smile']);
+
+        // 不正な設定は読み込まない
+        expect(() => production.importConfig({ ...config, wordStartBonus: NaN })).toThrow();
+        expect(() => production.importConfig({ tieBreak: 'random' as never })).toThrow();
+        expect(production.exportConfig()).toEqual(config);
+
+        // 以前のダンプの snake_case のキーも読み込める
+        production.importConfig({ word_start_bonus: 3 } as unknown as Partial<IndexConfig>);
+        expect(production.exportConfig().wordStartBonus).toBe(3);
+    });
+
+    it('Ranks matches on higher-weighted aliases first', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocumentWeighted('a_cat', [{ alias: 'kitty', weight: 0.5 }, { alias: 'neko', weight: 1 }]);
+        engine.addDocumentWeighted('b_cat', [{ alias: 'kitty', weight: 2 }]);
+        engine.setTieBreak('lexicographic');
+
+        expect(await engine.searchWithLimit('kitty', 10)).toEqual(['b_cat', 'a_cat']);
+
+        // 重みは dump / load で引き継がれる
+        const restored = await createSearchEngine();
+        restored.load(engine.dump());
+        expect(await restored.searchWithLimit('kitty', 10)).toEqual(['b_cat', 'a_cat']);
+
+        // 残ったエイリアスの重みは更新後も引き継がれる
+        engine.updateDocument('a_cat', ['kitty']);
+        engine.updateDocument('b_cat', ['kitty', 'mike']);
+        expect(await engine.searchWithLimit('kitty', 10)).toEqual(['b_cat', 'a_cat']);
+        expect(engine.verifyIntegrity().ok).toBe(true);
+
+        // 外したエイリアスの重みは捨てられ、付け直すと 1 になる
+        engine.updateDocument('a_cat', ['neko']);
+        engine.updateDocument('a_cat', ['kitty']);
+        engine.updateDocument('b_cat', ['mike']);
+        engine.updateDocument('b_cat', ['kitty']);
+        expect(await engine.searchWithLimit('kitty', 10)).toEqual(['a_cat', 'b_cat']);
+        expect(engine.verifyIntegrity().ok).toBe(true);
+
+        expect(() => engine.addDocumentWeighted('c_cat', [{ alias: 'kitty', weight: 0 }])).toThrow();
+        expect(engine.getDocLen('c_cat')).toBeNull();
+    });
+
+    it('Computes the overlap of two queries\' results', async () => {
+        const engine = await setupTestIndex();
+
+        expect(engine.resultOverlap('smile', 'smile', 10)).toBe(1);
+        expect(engine.resultOverlap('smile', 'cry', 10)).toBe(0);
+        // smile と joy はどちらも smile だけに一致する
+        expect(engine.resultOverlap('smile', 'joy', 10)).toBe(1);
+        expect(engine.resultOverlap('xyz', 'qqq', 10)).toBe(1);
+
+        // 大文字・小文字の一致を優先する場合も、それぞれのクエリの形で順位付けする
+        engine.addDocument('CAT', []);
+        engine.addDocument('cat', []);
+        engine.setPreferExactCase(true);
+        expect(engine.resultOverlap('CAT', 'cat', 1)).toBe(0);
+        expect(engine.resultOverlap('CAT', 'CAT', 1)).toBe(1);
+    });
+
+    it('Returns highlight ranges for the name and the matched alias', async () => {
+        const engine = await setupTestIndex();
+        engine.addDocument('cat', ['neko', 'feline']);
+        engine.addDocument(':blob_cat:', []);
+        engine.addDocument('🐱face', []);
+        engine.setStripChars(':');
+
+        expect(await engine.searchWithHighlights('eko', 10)).toEqual([
+            { id: 'cat', nameRanges: [], matchedAlias: 'neko', aliasRanges: [[1, 4]] },
+        ]);
+        const [alias] = await engine.searchWithHighlights('ega', 10);
+        expect(alias).toEqual({ id: '笑顔', nameRanges: [], matchedAlias: 'えがお', aliasRanges: [[0, 2]] });
+        expect('えがお'.slice(...alias.aliasRanges[0])).toBe('えが');
+
+        expect(await engine.searchWithHighlights('MIL', 10)).toEqual([
+            { id: 'smile', nameRanges: [[1, 4]], matchedAlias: null, aliasRanges: [] },
+        ]);
+        // 取り除く文字やサロゲートペアがあっても元の文字列の位置を指す
+        expect((await engine.searchWithHighlights('blob', 10))[0].nameRanges).toEqual([[1, 5]]);
+        const [emoji] = await engine.searchWithHighlights('face', 10);
+        expect('🐱face'.slice(...emoji.nameRanges[0])).toBe('face');
+    });
+
+    it('Breaks ties by alias count', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('cat_a', ['neko']);
+        engine.addDocument('cat_b', ['neko', 'kitty', 'feline']);
+        engine.addDocument('cat_c', ['neko', 'kitty']);
+        engine.setTieBreak('aliasCount');
+
+        expect(await engine.searchWithLimit('cat', 10)).toEqual(['cat_b', 'cat_c', 'cat_a']);
+
+        // 設定は dump / load で引き継がれる
+        const restored = await createSearchEngine();
+        restored.load(engine.dump());
+        expect(await restored.searchWithLimit('cat', 10)).toEqual(['cat_b', 'cat_c', 'cat_a']);
+    });
+
+    it('Lists documents that only partially match their own name', async () => {
+        const engine = await createSearchEngine();
+        engine.addDocument('smile', ['happy']);
+        engine.addDocument('happy cat', []);
+        engine.addDocument('secret', ['topsecret'], false);
+        engine.addDocument('hidden', ['hidden_face'], false);
+
+        // 名前に空白を含むものは AND 検索になり、名前を検索できないものはエイリアスの部分一致にしかならない
+        expect(engine.documentsOnlyPartiallySearchable()).toEqual(['happy cat', 'secret']);
+        expect(await engine.searchWithLabels('secret', 10)).toEqual([{ id: 'secret', priority: 'alias_partial' }]);
+    });
+
+    it('Resets all settings to their defaults without touching documents', async () => {
+        const engine = await setupTestIndex();
+        const fresh = await setupTestIndex();
+        const defaults = fresh.exportConfig();
+
+        engine.setKanaConversion(false);
+        engine.setStripChars(':');
+        engine.setMaxLimit(1);
+        engine.setTieBreak('lexicographic');
+        engine.setWordStartBonus(3);
+        engine.addKanaOverride('uu', 'う');
+        engine.setSuppressPartialsOnExact(true);
+        expect(engine.exportConfig()).not.toEqual(defaults);
+
+        engine.resetAllSettings();
+        expect(engine.exportConfig()).toEqual(defaults);
+        expect(engine.getDocumentCount()).toBe(3);
+        expect(engine.fingerprint()).toBe(fresh.fingerprint());
+        for (const query of ['smile', 'egao', 'sa', ':cry:']) {
+            expect((await engine.searchWithLimit(query, 10)).sort()).toEqual((await fresh.searchWithLimit(query, 10)).sort());
+        }
+    });
+
+    it('Rejects corrupt or foreign blobs with a specific error', async () => {
+        const engine = await setupTestIndex();
+        const dump = engine.dump();
+        const target = await createSearchEngine();
+
+        expect(() => target.load(dump.slice(0, dump.length - 5))).toThrow(/truncated/);
+        expect(() => target.load(dump.slice(0, 10))).toThrow(/truncated/);
+        expect(() => target.load(new TextEncoder().encode('PK\x03\x04 definitely not an index'))).toThrow(/not a hanami index/);
+        expect(() => target.load(new Uint8Array())).toThrow(/not a hanami index/);
+        // 空の旧形式のインデックスとして読めてしまうバイト列も受け付けない
+        expect(() => target.load(new Uint8Array(16))).toThrow(/not a hanami index/);
+        expect(() => target.load(new Uint8Array(64))).toThrow(/not a hanami index/);
+
+        const corrupted = dump.slice();
+        corrupted[corrupted.length - 1] ^= 0xff;
+        expect(() => target.load(corrupted)).toThrow(/checksum failed/);
+
+        // バージョン欄（識別子の直後の4バイト）を読み込めないほど新しくする
+        const future = dump.slice();
+        new DataView(future.buffer).setUint32(4, 0xffff, true);
+        expect(() => target.load(future)).toThrow(/version mismatch/);
+
+        // 失敗しても元のインデックスは変わらず、正しいデータは読み込める
+        expect(target.getDocumentCount()).toBe(0);
+        target.load(dump);
+        expect(await target.searchWithLimit('smile', 10)).toEqual(['smile']);
+    });
+});